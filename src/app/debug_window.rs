@@ -43,6 +43,14 @@ impl super::PaintApp {
                             .text("Vertical scroll"),
                     );
                 });
+                if let Some(info) = self
+                    .debug
+                    .figure_index
+                    .and_then(|index| self.drawing.figures[index].as_debug())
+                    .and_then(|figure| figure.info())
+                {
+                    ui.label(info);
+                }
                 self.draw_debug_grid(ui);
             });
         }
@@ -1,26 +1,143 @@
-use super::{draw_pixels, Drawable, EditableControlPoints, Figure, Selectable};
-use crate::curves::{generate_bezier_curve, generate_bspline_curve, generate_hermite_curve};
+use super::{
+    draw_pixels, CurveTransform, Debuggable, Drawable, EditableControlPoints, Figure, Selectable,
+};
+use crate::curves::{
+    arc_length_table, bspline_segments, curvature, curvature_features, flatten_nurbs,
+    flatten_segments, hermite_segment, intersect_pieces, kochanek_bartels_segments, locate_segment,
+    nurbs_circle, nurbs_control_net, nurbs_derivatives, nurbs_domain, nurbs_knots, nurbs_pieces,
+    nurbs_point, offset_segments, parameter_at_length, rasterize_polyline, segment_pieces,
+    segments_derivatives, segments_point, self_intersections, split_nurbs, BezierSegment,
+    CurvePiece, Derivatives, Intersection, KnotVector, LineAlgorithm, NurbsNet,
+};
 use crate::pixel::Pixel;
-use eframe::egui::Pos2;
+use eframe::egui::{Align2, Color32, FontId, Pos2, Vec2};
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum CurveType {
     Hermite,
     Bezier,
     BSpline,
+    Nurbs,
+    CatmullRom,
+    Cardinal,
+    KochanekBartels,
+    BezierPath,
+}
+
+impl CurveType {
+    pub fn point_count(&self) -> Option<usize> {
+        match self {
+            CurveType::Hermite | CurveType::Bezier => Some(4),
+            CurveType::BSpline
+            | CurveType::Nurbs
+            | CurveType::CatmullRom
+            | CurveType::Cardinal
+            | CurveType::KochanekBartels
+            | CurveType::BezierPath => None,
+        }
+    }
+
+    pub fn min_points(&self) -> usize {
+        match self {
+            CurveType::BSpline | CurveType::BezierPath => 4,
+            _ => self.point_count().unwrap_or(2),
+        }
+    }
+}
+
+pub struct NurbsParameters {
+    pub degree: usize,
+    pub weights: Vec<f32>,
+    pub knots: Vec<f32>,
+    pub knot_vector: KnotVector,
+}
+
+impl NurbsParameters {
+    fn new(control_count: usize) -> Self {
+        let mut new = Self {
+            degree: 3.min(control_count.saturating_sub(1)).max(1),
+            weights: vec![1.0; control_count],
+            knots: vec![],
+            knot_vector: KnotVector::Clamped,
+        };
+        new.reset_knots(control_count);
+        new
+    }
+
+    pub fn reset_knots(&mut self, control_count: usize) {
+        self.degree = self.degree.clamp(1, control_count.saturating_sub(1).max(1));
+        self.knots = nurbs_knots(self.knot_vector, control_count, self.degree);
+    }
+}
+
+#[derive(Default)]
+pub struct SplineParameters {
+    pub tension: f32,
+    pub continuity: f32,
+    pub bias: f32,
+}
+
+pub struct CurveAnalysis {
+    pub tangents: bool,
+    pub normals: bool,
+    pub comb: bool,
+    pub features: bool,
+    pub comb_scale: f32,
+    pub samples: usize,
+}
+
+impl Default for CurveAnalysis {
+    fn default() -> Self {
+        Self {
+            tangents: false,
+            normals: false,
+            comb: false,
+            features: false,
+            comb_scale: 2000.0,
+            samples: 64,
+        }
+    }
+}
+
+impl CurveAnalysis {
+    fn is_enabled(&self) -> bool {
+        self.tangents || self.normals || self.comb || self.features
+    }
 }
 
 pub struct Curve {
     points: Vec<Pixel>,
-    control_points: [Pos2; 4],
+    control_points: Vec<Pos2>,
     selected: bool,
     curve_type: CurveType,
     selected_point: Option<usize>,
+    nurbs: NurbsParameters,
+    spline: SplineParameters,
+    bspline_mode: KnotVector,
+    tolerance: f32,
+    line_algorithm: LineAlgorithm,
+    markers: Vec<Pos2>,
+    labels: Vec<(Pos2, String)>,
+    probe: Option<(Pos2, Pos2)>,
+    analysis: CurveAnalysis,
+    stroke: Vec<Pos2>,
+    debug_step: Option<usize>,
 }
 
 impl Curve {
-    pub fn new(control_points: [Pos2; 4], curve_type: CurveType) -> Self {
+    pub fn new(control_points: Vec<Pos2>, curve_type: CurveType) -> Self {
         let mut new = Self {
+            nurbs: NurbsParameters::new(control_points.len()),
+            spline: SplineParameters::default(),
+            bspline_mode: KnotVector::Periodic,
+            tolerance: Self::DEFAULT_TOLERANCE,
+            line_algorithm: LineAlgorithm::Bresenham,
+            markers: vec![],
+            labels: vec![],
+            probe: None,
+            analysis: CurveAnalysis::default(),
+            stroke: vec![],
+            debug_step: None,
             control_points,
             points: vec![],
             selected: false,
@@ -31,15 +148,134 @@ impl Curve {
         new
     }
 
+    // Bezier path fitted to a freehand stroke; the stroke itself is drawn
+    // underneath when kept.
+    pub fn fitted(segments: &[BezierSegment], stroke: Option<Vec<Pos2>>) -> Self {
+        let mut new = Self::new(path_points(segments), CurveType::BezierPath);
+        if let Some(stroke) = stroke {
+            new.stroke = stroke;
+            new.update_render();
+        }
+        new
+    }
+
+    // Bezier path through the given points with straight edges.
+    pub fn polyline(points: &[Pos2]) -> Self {
+        let segments: Vec<BezierSegment> = points
+            .windows(2)
+            .map(|edge| {
+                let (a, b) = (edge[0], edge[1]);
+                BezierSegment::new(vec![a, a.lerp(b, 1.0 / 3.0), a.lerp(b, 2.0 / 3.0), b])
+            })
+            .collect();
+        Self::new(path_points(&segments), CurveType::BezierPath)
+    }
+
     fn update_render(&mut self) {
         self.points.clear();
+        if let Some(step) = self.debug_step {
+            self.draw_debug_step(step);
+            return;
+        }
+        draw_overlay_line(&mut self.points, &self.stroke, (160, 160, 160, 255));
         self.generate_curve();
 
+        if let Some((start, end)) = self.probe {
+            draw_overlay_line(&mut self.points, &[start, end], (0, 0, 0, 255));
+        }
+        for marker in self.markers.iter() {
+            Self::draw_circle(&mut self.points, *marker, (0, 0, 255, 255));
+        }
+        if self.selected && self.analysis.is_enabled() {
+            let overlay = self.analysis_overlay();
+            self.points.extend(overlay);
+        }
         if self.selected {
             self.draw_control_points();
         }
     }
 
+    // Traces the curve up to sample `step`, with the control points that
+    // influence the current point highlighted and the current segment's
+    // Bezier polygon drawn in grey.
+    fn draw_debug_step(&mut self, step: usize) {
+        let t = step as f32 / Self::DEBUG_SAMPLES as f32;
+        let (polyline, current) = {
+            let evaluate = self.evaluator();
+            let polyline: Vec<Pos2> = (0..=step)
+                .map(|i| evaluate(i as f32 / Self::DEBUG_SAMPLES as f32))
+                .collect();
+            (polyline, evaluate(t))
+        };
+        if let Some(segment) = self.debug_segment(t) {
+            draw_overlay_line(&mut self.points, &segment.points, (150, 150, 150, 255));
+        }
+        rasterize_polyline(&polyline, self.line_algorithm, &mut self.points);
+
+        let weights = self.basis_weights(t);
+        for (pos, weight) in self.control_points.iter().zip(weights) {
+            let color = if weight.abs() > Self::ACTIVE_WEIGHT {
+                (255, 140, 0, 255)
+            } else {
+                (255, 0, 0, 255)
+            };
+            Self::draw_circle(&mut self.points, *pos, color);
+        }
+        Self::draw_circle(&mut self.points, current, (0, 0, 255, 255));
+    }
+
+    fn debug_segment(&self, t: f32) -> Option<BezierSegment> {
+        let segments = self.segments();
+        if segments.is_empty() {
+            return None;
+        }
+        let (index, _) = locate_segment(segments.len(), t);
+        Some(segments[index].clone())
+    }
+
+    fn analysis_overlay(&self) -> Vec<Pixel> {
+        let analysis = &self.analysis;
+        let mut overlay = vec![];
+        let evaluate = self.derivative_evaluator();
+        let mut envelope = vec![];
+        for i in 0..=analysis.samples {
+            let (point, first, second) = evaluate(i as f32 / analysis.samples as f32);
+            if first.length() < 1e-6 {
+                continue;
+            }
+            let tangent = first.normalized();
+            let kappa = curvature(first, second);
+            if analysis.tangents {
+                let end = point + tangent * Self::VECTOR_LENGTH;
+                draw_overlay_line(&mut overlay, &[point, end], (0, 160, 0, 255));
+            }
+            if analysis.normals {
+                // Principal normal, pointing to the centre of curvature.
+                let normal = -tangent.rot90() * if kappa < 0.0 { -1.0 } else { 1.0 };
+                let end = point + normal * Self::VECTOR_LENGTH;
+                draw_overlay_line(&mut overlay, &[point, end], (0, 0, 200, 255));
+            }
+            if analysis.comb {
+                // Teeth point away from the centre of curvature.
+                let tip = point + tangent.rot90() * kappa * analysis.comb_scale;
+                draw_overlay_line(&mut overlay, &[point, tip], (150, 150, 150, 255));
+                envelope.push(tip);
+            }
+        }
+        draw_overlay_line(&mut overlay, &envelope, (200, 0, 200, 255));
+
+        if analysis.features {
+            let (inflections, extrema) = curvature_features(&evaluate, Self::FEATURE_SAMPLES);
+            for t in inflections {
+                Self::draw_circle(&mut overlay, evaluate(t).0, (255, 140, 0, 255));
+            }
+            for t in extrema {
+                Self::draw_circle(&mut overlay, evaluate(t).0, (0, 180, 180, 255));
+            }
+        }
+        overlay
+    }
+
     fn bounding_box(&self) -> (Pos2, Pos2) {
         self.points.iter().fold(
             (
@@ -56,6 +292,12 @@ impl Curve {
     }
 
     fn draw_control_points(&mut self) {
+        if self.curve_type == CurveType::Hermite {
+            for endpoint in [0, 2] {
+                let handle = &self.control_points[endpoint..=endpoint + 1];
+                draw_arrow(&mut self.points, handle[0], handle[1], (0, 120, 0, 255));
+            }
+        }
         for (index, pos) in self.control_points.iter().enumerate() {
             let is_selected = self.selected_point == Some(index);
             let color = if is_selected {
@@ -93,28 +335,279 @@ impl Curve {
         }
     }
 
-    fn generate_curve(&mut self) {
+    fn segments(&self) -> Vec<BezierSegment> {
+        self.segments_of(&self.control_points)
+    }
+
+    fn segments_of(&self, points: &[Pos2]) -> Vec<BezierSegment> {
         match self.curve_type {
-            CurveType::Bezier => generate_bezier_curve(
-                self.control_points[0],
-                self.control_points[1],
-                self.control_points[2],
-                self.control_points[3],
-                &mut self.points,
+            CurveType::Bezier => vec![BezierSegment::new(points.to_vec())],
+            CurveType::Hermite => {
+                let [t0, t1] = Self::tangents(points);
+                vec![hermite_segment(points[0], t0, points[2], t1)]
+            }
+            CurveType::BSpline => bspline_segments(points, self.bspline_mode),
+            CurveType::CatmullRom => kochanek_bartels_segments(points, 0.0, 0.0, 0.0),
+            CurveType::Cardinal => kochanek_bartels_segments(points, self.spline.tension, 0.0, 0.0),
+            CurveType::KochanekBartels => kochanek_bartels_segments(
+                points,
+                self.spline.tension,
+                self.spline.continuity,
+                self.spline.bias,
             ),
-            CurveType::Hermite => generate_hermite_curve(
-                self.control_points[0],
-                self.control_points[1],
-                self.control_points[2],
-                self.control_points[3],
-                &mut self.points,
+            CurveType::BezierPath => points
+                .windows(4)
+                .step_by(3)
+                .map(|p| BezierSegment::new(p.to_vec()))
+                .collect(),
+            CurveType::Nurbs => vec![],
+        }
+    }
+
+    // Hermite control points are [p0, h0, p1, h1]: each handle is the tip of
+    // the tangent arrow, so the arrow is the tangent vector itself.
+    fn tangents(points: &[Pos2]) -> [Vec2; 2] {
+        [points[1] - points[0], points[3] - points[2]]
+    }
+
+    fn nurbs_net(&self) -> (Vec<Pos2>, Vec<f32>) {
+        nurbs_control_net(
+            &self.control_points,
+            &self.nurbs.weights,
+            self.nurbs.degree,
+            self.nurbs.knot_vector,
+        )
+    }
+
+    // Evaluates the curve at a global parameter in [0, 1].
+    fn evaluator(&self) -> Box<dyn Fn(f32) -> Pos2 + '_> {
+        match self.curve_type {
+            CurveType::Nurbs => {
+                let (points, weights) = self.nurbs_net();
+                let (degree, knots) = (self.nurbs.degree, &self.nurbs.knots);
+                let (start, end) = nurbs_domain(knots, degree, points.len());
+                Box::new(move |t| {
+                    nurbs_point(&points, &weights, knots, degree, start + (end - start) * t)
+                })
+            }
+            _ => {
+                let segments = self.segments();
+                Box::new(move |t| segments_point(&segments, t))
+            }
+        }
+    }
+
+    // Every curve type is linear in its control points, so the weight of
+    // point j at `t` is the curve evaluated with point j at x = 1 and all
+    // others at the origin.
+    fn basis_weights(&self, t: f32) -> Vec<f32> {
+        let count = self.control_points.len();
+        (0..count)
+            .map(|j| {
+                let mut unit = vec![Pos2::ZERO; count];
+                unit[j] = Pos2::new(1.0, 0.0);
+                match self.curve_type {
+                    CurveType::Nurbs => {
+                        let (points, weights) = nurbs_control_net(
+                            &unit,
+                            &self.nurbs.weights,
+                            self.nurbs.degree,
+                            self.nurbs.knot_vector,
+                        );
+                        let (degree, knots) = (self.nurbs.degree, &self.nurbs.knots);
+                        let (start, end) = nurbs_domain(knots, degree, points.len());
+                        let u = start + (end - start) * t;
+                        nurbs_point(&points, &weights, knots, degree, u).x
+                    }
+                    _ => segments_point(&self.segments_of(&unit), t).x,
+                }
+            })
+            .collect()
+    }
+
+    // First and second derivatives at a global parameter in [0, 1], taken from
+    // the Bezier form of each segment or the rational basis of the NURBS.
+    fn derivative_evaluator(&self) -> Box<dyn Fn(f32) -> Derivatives + '_> {
+        match self.curve_type {
+            CurveType::Nurbs => {
+                let (points, weights) = self.nurbs_net();
+                let (degree, knots) = (self.nurbs.degree, &self.nurbs.knots);
+                let (start, end) = nurbs_domain(knots, degree, points.len());
+                let scale = end - start;
+                Box::new(move |t| {
+                    let u = start + scale * t;
+                    let (point, first, second) =
+                        nurbs_derivatives(&points, &weights, knots, degree, u);
+                    (point, first * scale, second * scale * scale)
+                })
+            }
+            _ => {
+                let segments = self.segments();
+                Box::new(move |t| segments_derivatives(&segments, t))
+            }
+        }
+    }
+
+    fn pieces(&self) -> Vec<CurvePiece> {
+        match self.curve_type {
+            CurveType::Nurbs => nurbs_pieces(
+                &self.control_points,
+                &self.nurbs.weights,
+                &self.nurbs.knots,
+                self.nurbs.degree,
+                self.nurbs.knot_vector,
             ),
-            CurveType::BSpline => generate_bspline_curve(&self.control_points, &mut self.points),
+            _ => segment_pieces(&self.segments()),
+        }
+    }
+
+    // Parameters in [0, 1] where the curve may be only C0: segment joints or
+    // distinct knots inside the NURBS domain.
+    fn breaks(&self) -> Vec<f32> {
+        match self.curve_type {
+            CurveType::Nurbs => {
+                let knots = &self.nurbs.knots;
+                let count = knots.len() - self.nurbs.degree - 1;
+                let (start, end) = nurbs_domain(knots, self.nurbs.degree, count);
+                let mut breaks: Vec<f32> = knots
+                    .iter()
+                    .filter(|k| **k >= start && **k <= end)
+                    .map(|k| (k - start) / (end - start))
+                    .collect();
+                breaks.dedup();
+                breaks
+            }
+            _ => {
+                let count = self.segments().len();
+                (0..=count).map(|i| i as f32 / count as f32).collect()
+            }
         }
     }
 
+    fn is_closed(&self) -> bool {
+        let evaluate = self.evaluator();
+        evaluate(0.0).distance(evaluate(1.0)) < Self::CLOSED_DISTANCE
+    }
+
+    fn offset_of(&self, distance: f32) -> Vec<BezierSegment> {
+        offset_segments(
+            self.derivative_evaluator(),
+            &self.breaks(),
+            self.is_closed(),
+            distance,
+            self.tolerance,
+        )
+    }
+
+    fn clear_markers(&mut self) {
+        self.markers.clear();
+        self.labels.clear();
+        self.probe = None;
+    }
+
+    // Marks every intersection with its parameter on this curve and on the
+    // other one.
+    fn show_intersections(&mut self, intersections: &[Intersection], other: &str) -> usize {
+        self.clear_markers();
+        for hit in intersections {
+            let touch = if hit.tangential { " (touch)" } else { "" };
+            let text = format!(
+                "t = {:.3}, {} = {:.3}{}",
+                hit.first, other, hit.second, touch
+            );
+            self.markers.push(hit.point);
+            self.labels.push((hit.point, text));
+        }
+        intersections.len()
+    }
+
+    // New curve sharing this one's rendering settings.
+    fn derived(&self, control_points: Vec<Pos2>, curve_type: CurveType) -> Curve {
+        let mut curve = Curve::new(control_points, curve_type);
+        curve.tolerance = self.tolerance;
+        curve.line_algorithm = self.line_algorithm;
+        curve.update_render();
+        curve
+    }
+
+    fn derived_nurbs(&self, (points, weights, knots): NurbsNet) -> Curve {
+        let mut curve = self.derived(points, CurveType::Nurbs);
+        curve.nurbs = NurbsParameters {
+            degree: self.nurbs.degree,
+            weights,
+            knots,
+            knot_vector: match self.nurbs.knot_vector {
+                KnotVector::Periodic => KnotVector::Open,
+                kind => kind,
+            },
+        };
+        curve.update_render();
+        curve
+    }
+
+    fn generate_curve(&mut self) {
+        let polyline = match self.curve_type {
+            CurveType::Nurbs => flatten_nurbs(
+                &self.control_points,
+                &self.nurbs.weights,
+                &self.nurbs.knots,
+                self.nurbs.degree,
+                self.nurbs.knot_vector,
+                self.tolerance,
+            ),
+            _ => flatten_segments(&self.segments(), self.tolerance),
+        };
+        rasterize_polyline(&polyline, self.line_algorithm, &mut self.points);
+    }
+
     const THICKNESS: f32 = 2.5;
     const THREASHOLD_HIT: f32 = 3.0;
+    const DEFAULT_TOLERANCE: f32 = 0.5;
+    const ARC_LENGTH_SAMPLES: usize = 1024;
+    const FEATURE_SAMPLES: usize = 256;
+    const VECTOR_LENGTH: f32 = 25.0;
+    const CLOSED_DISTANCE: f32 = 1e-3;
+    const DEBUG_SAMPLES: usize = 100;
+    const ACTIVE_WEIGHT: f32 = 1e-6;
+    const ARROW_HEAD: f32 = 8.0;
+}
+
+fn draw_overlay_line(pixels: &mut Vec<Pixel>, polyline: &[Pos2], color: (u8, u8, u8, u8)) {
+    let start = pixels.len();
+    rasterize_polyline(polyline, LineAlgorithm::Bresenham, pixels);
+    for pixel in pixels[start..].iter_mut() {
+        (pixel.red, pixel.green, pixel.blue, pixel.intensity) = color;
+    }
+}
+
+fn draw_arrow(pixels: &mut Vec<Pixel>, start: Pos2, end: Pos2, color: (u8, u8, u8, u8)) {
+    draw_overlay_line(pixels, &[start, end], color);
+    if start.distance(end) < 1.0 {
+        return;
+    }
+    let back = (start - end).normalized() * Curve::ARROW_HEAD;
+    let side = back.rot90() * 0.5;
+    draw_overlay_line(pixels, &[end + back + side, end, end + back - side], color);
+}
+
+// Hermite input layout for a cubic Bezier: the end tangents are three times
+// the outer legs of the control polygon.
+fn hermite_points(segment: &BezierSegment) -> Vec<Pos2> {
+    let b = &segment.points;
+    vec![
+        b[0],
+        b[0] + (b[1] - b[0]) * 3.0,
+        b[3],
+        b[3] + (b[3] - b[2]) * 3.0,
+    ]
+}
+
+fn path_points(segments: &[BezierSegment]) -> Vec<Pos2> {
+    let mut points = vec![segments[0].start()];
+    for segment in segments {
+        points.extend_from_slice(&segment.points[1..]);
+    }
+    points
 }
 
 impl Figure for Curve {
@@ -133,6 +626,84 @@ impl Figure for Curve {
     fn as_editable_points_mut(&mut self) -> Option<&mut dyn EditableControlPoints> {
         Some(self)
     }
+
+    fn as_curve_transform(&self) -> Option<&dyn CurveTransform> {
+        Some(self)
+    }
+
+    fn as_curve_transform_mut(&mut self) -> Option<&mut dyn CurveTransform> {
+        Some(self)
+    }
+
+    fn as_debug(&self) -> Option<&dyn Debuggable> {
+        Some(self)
+    }
+
+    fn as_debug_mut(&mut self) -> Option<&mut dyn Debuggable> {
+        Some(self)
+    }
+}
+
+impl Debuggable for Curve {
+    fn update_frame(&mut self) -> bool {
+        let step = self.debug_step.map_or(0, |step| step + 1);
+        if step > Self::DEBUG_SAMPLES {
+            self.evaluate();
+            return false;
+        }
+        self.debug_step = Some(step);
+        self.update_render();
+        true
+    }
+
+    fn evaluate(&mut self) {
+        self.debug_step = None;
+        self.update_render();
+    }
+
+    fn get_offset(&self) -> Pos2 {
+        let start = Pos2::new(f32::INFINITY, f32::INFINITY);
+        self.control_points.iter().fold(start, |min, p| min.min(*p))
+    }
+
+    fn get_pixels(&self) -> &[Pixel] {
+        self.points.as_slice()
+    }
+
+    fn info(&self) -> Option<String> {
+        let step = self.debug_step?;
+        let t = step as f32 / Self::DEBUG_SAMPLES as f32;
+        let mut text = format!("t = {:.2}\n", t);
+        match self.curve_type {
+            CurveType::Nurbs => {
+                let (start, end) = nurbs_domain(
+                    &self.nurbs.knots,
+                    self.nurbs.degree,
+                    self.nurbs_net().0.len(),
+                );
+                text += &format!("u = {:.3}\n", start + (end - start) * t);
+            }
+            _ => {
+                let count = self.segments().len();
+                let (index, local) = locate_segment(count, t);
+                text += &format!("Segment {} of {}\n", index + 1, count);
+                text += &format!("Local t = {:.3}\n", local);
+            }
+        }
+
+        let weights = self.basis_weights(t);
+        let active: Vec<usize> = (0..weights.len())
+            .filter(|i| weights[*i].abs() > Self::ACTIVE_WEIGHT)
+            .collect();
+        if let (Some(first), Some(last)) = (active.first(), active.last()) {
+            text += &format!("Active points: P{}..P{}\n", first, last);
+        }
+        text += "Basis weights:";
+        for i in active {
+            text += &format!("\n  P{}: {:.4}", i, weights[i]);
+        }
+        Some(text)
+    }
 }
 
 impl Selectable for Curve {
@@ -202,9 +773,20 @@ impl EditableControlPoints for Curve {
         self.update_render();
     }
 
+    fn select_point(&mut self, index: usize) {
+        self.selected_point = Some(index);
+        self.update_render();
+    }
+
     fn move_point(&mut self, pos: Pos2) -> bool {
         if let Some(index) = self.selected_point {
+            // Hermite endpoints carry their tangent handles along.
+            if self.curve_type == CurveType::Hermite && index % 2 == 0 {
+                let offset = pos - self.control_points[index];
+                self.control_points[index + 1] += offset;
+            }
             self.control_points[index] = pos;
+            self.clear_markers();
             self.update_render();
             return true;
         }
@@ -212,6 +794,267 @@ impl EditableControlPoints for Curve {
     }
 }
 
+impl CurveTransform for Curve {
+    fn selected_point(&self) -> Option<usize> {
+        self.selected_point
+    }
+
+    fn nurbs_mut(&mut self) -> Option<&mut NurbsParameters> {
+        match self.curve_type {
+            CurveType::Nurbs => Some(&mut self.nurbs),
+            _ => None,
+        }
+    }
+
+    fn spline_mut(&mut self) -> Option<&mut SplineParameters> {
+        match self.curve_type {
+            CurveType::Cardinal | CurveType::KochanekBartels => Some(&mut self.spline),
+            _ => None,
+        }
+    }
+
+    fn bspline_mode_mut(&mut self) -> Option<&mut KnotVector> {
+        match self.curve_type {
+            CurveType::BSpline => Some(&mut self.bspline_mode),
+            _ => None,
+        }
+    }
+
+    fn has_continuity(&self) -> bool {
+        self.curve_type == CurveType::KochanekBartels
+    }
+
+    fn tolerance_mut(&mut self) -> &mut f32 {
+        &mut self.tolerance
+    }
+
+    fn line_algorithm_mut(&mut self) -> &mut LineAlgorithm {
+        &mut self.line_algorithm
+    }
+
+    fn refresh(&mut self) {
+        self.clear_markers();
+        self.update_render();
+    }
+
+    fn hermite_tangents(&self) -> Option<[Vec2; 2]> {
+        match self.curve_type {
+            CurveType::Hermite => Some(Self::tangents(&self.control_points)),
+            _ => None,
+        }
+    }
+
+    fn set_hermite_tangents(&mut self, tangents: [Vec2; 2]) {
+        if self.curve_type != CurveType::Hermite {
+            return;
+        }
+        for (endpoint, tangent) in [0, 2].into_iter().zip(tangents) {
+            self.control_points[endpoint + 1] = self.control_points[endpoint] + tangent;
+        }
+        self.clear_markers();
+        self.update_render();
+    }
+
+    fn analysis_mut(&mut self) -> &mut CurveAnalysis {
+        &mut self.analysis
+    }
+
+    fn redraw(&mut self) {
+        self.update_render();
+    }
+
+    fn make_circle(&mut self) {
+        let count = self.control_points.len() as f32;
+        let center = self
+            .control_points
+            .iter()
+            .fold(Pos2::ZERO, |acc, p| acc + p.to_vec2() / count);
+        let radius = self
+            .control_points
+            .iter()
+            .map(|p| p.distance(center))
+            .sum::<f32>()
+            / count;
+
+        let (points, weights, knots) = nurbs_circle(center, radius.max(1.0));
+        self.control_points = points;
+        self.nurbs = NurbsParameters {
+            degree: 2,
+            weights,
+            knots,
+            knot_vector: KnotVector::Clamped,
+        };
+        self.selected_point = None;
+        self.clear_markers();
+        self.update_render();
+    }
+
+    fn nearest_parameter(&self, pos: Pos2) -> f32 {
+        let evaluate = self.evaluator();
+        (0..=Self::ARC_LENGTH_SAMPLES)
+            .map(|i| i as f32 / Self::ARC_LENGTH_SAMPLES as f32)
+            .map(|t| (t, evaluate(t).distance(pos)))
+            .filter(|(_, distance)| distance.is_finite())
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0.0, |(t, _)| t)
+    }
+
+    fn split(&self, t: f32) -> Option<(Curve, Curve)> {
+        if t <= 0.0 || t >= 1.0 {
+            return None;
+        }
+        match self.curve_type {
+            CurveType::Nurbs => {
+                let (points, weights) = self.nurbs_net();
+                let (degree, knots) = (self.nurbs.degree, &self.nurbs.knots);
+                let (start, end) = nurbs_domain(knots, degree, points.len());
+                let u = start + (end - start) * t;
+                let (left, right) = split_nurbs(&points, &weights, knots, degree, u)?;
+                Some((self.derived_nurbs(left), self.derived_nurbs(right)))
+            }
+            CurveType::Bezier => {
+                let (left, right) = BezierSegment::new(self.control_points.clone()).split(t);
+                Some((
+                    self.derived(left.points, CurveType::Bezier),
+                    self.derived(right.points, CurveType::Bezier),
+                ))
+            }
+            CurveType::Hermite => {
+                let (left, right) = self.segments()[0].split(t);
+                Some((
+                    self.derived(hermite_points(&left), CurveType::Hermite),
+                    self.derived(hermite_points(&right), CurveType::Hermite),
+                ))
+            }
+            _ => {
+                let segments = self.segments();
+                let (index, local) = locate_segment(segments.len(), t);
+                let (mut first, mut second) = (segments[..index].to_vec(), vec![]);
+                if local > 0.0 {
+                    let (left, right) = segments[index].split(local);
+                    first.push(left);
+                    second.push(right);
+                } else {
+                    second.push(segments[index].clone());
+                }
+                second.extend_from_slice(&segments[index + 1..]);
+                Some((
+                    self.derived(path_points(&first), CurveType::BezierPath),
+                    self.derived(path_points(&second), CurveType::BezierPath),
+                ))
+            }
+        }
+    }
+
+    fn elevate_degree(&mut self) -> bool {
+        if self.curve_type != CurveType::Bezier {
+            return false;
+        }
+        self.control_points = BezierSegment::new(self.control_points.clone())
+            .elevate()
+            .points;
+        self.selected_point = None;
+        self.update_render();
+        true
+    }
+
+    fn reverse(&mut self) {
+        let points = &mut self.control_points;
+        match self.curve_type {
+            CurveType::Hermite => {
+                let (p0, h0, p1, h1) = (points[0], points[1], points[2], points[3]);
+                *points = vec![p1, p1 + (p1 - h1), p0, p0 + (p0 - h0)];
+            }
+            CurveType::Nurbs => {
+                points.reverse();
+                self.nurbs.weights.reverse();
+                let knots = &mut self.nurbs.knots;
+                let (first, last) = (knots[0], knots[knots.len() - 1]);
+                *knots = knots.iter().rev().map(|k| first + last - k).collect();
+            }
+            CurveType::KochanekBartels => {
+                points.reverse();
+                self.spline.bias = -self.spline.bias;
+            }
+            _ => points.reverse(),
+        }
+        self.selected_point = None;
+        self.update_render();
+    }
+
+    fn arc_length_table(&self) -> Vec<(f32, f32)> {
+        arc_length_table(self.evaluator(), Self::ARC_LENGTH_SAMPLES)
+    }
+
+    fn pieces(&self) -> Vec<CurvePiece> {
+        Curve::pieces(self)
+    }
+
+    fn intersect_curve(&mut self, other: &[CurvePiece]) -> usize {
+        let intersections = intersect_pieces(&self.pieces(), other);
+        let count = self.show_intersections(&intersections, "s");
+        self.update_render();
+        count
+    }
+
+    fn intersect_segment(&mut self, start: Pos2, end: Pos2) -> usize {
+        let intersections = intersect_pieces(&self.pieces(), &[CurvePiece::line(start, end)]);
+        let count = self.show_intersections(&intersections, "s");
+        self.probe = Some((start, end));
+        self.update_render();
+        count
+    }
+
+    fn self_intersect(&mut self) -> usize {
+        let intersections = self_intersections(&self.pieces(), self.is_closed());
+        let count = self.show_intersections(&intersections, "t");
+        self.update_render();
+        count
+    }
+
+    fn offset(&self, distance: f32) -> Vec<Curve> {
+        [distance, -distance]
+            .into_iter()
+            .map(|d| self.offset_of(d))
+            .filter(|segments| !segments.is_empty())
+            .map(|segments| self.derived(path_points(&segments), CurveType::BezierPath))
+            .collect()
+    }
+
+    // Closed polygon bounding the stroke of the given half-width. An open
+    // curve gets butt caps; a closed one becomes a keyhole joining the outer
+    // and inner offsets, which the even-odd fills treat as a ring.
+    fn stroke_outline(&self, distance: f32) -> Vec<Pos2> {
+        let left = flatten_segments(&self.offset_of(distance), self.tolerance);
+        let mut right = flatten_segments(&self.offset_of(-distance), self.tolerance);
+        if left.is_empty() || right.is_empty() {
+            return vec![];
+        }
+        right.reverse();
+        let mut outline = left;
+        if self.is_closed() {
+            outline.push(outline[0]);
+            outline.extend_from_slice(&right);
+            outline.push(right[0]);
+        } else {
+            outline.extend(right);
+        }
+        outline
+    }
+
+    fn resample(&self, count: usize) -> Vec<Pos2> {
+        let table = self.arc_length_table();
+        let total = table.last().map_or(0.0, |(_, s)| *s);
+        let evaluate = self.evaluator();
+        (0..count)
+            .map(|i| {
+                let length = total * i as f32 / (count - 1).max(1) as f32;
+                evaluate(parameter_at_length(&table, length))
+            })
+            .collect()
+    }
+}
+
 fn distance_to_line_segment(p1: Pos2, p2: Pos2, point: Pos2) -> f32 {
     let v = p2 - p1;
     let u = point - p1;
@@ -224,5 +1067,14 @@ fn distance_to_line_segment(p1: Pos2, p2: Pos2, point: Pos2) -> f32 {
 impl Drawable for Curve {
     fn draw(&self, painter: &eframe::egui::Painter) {
         draw_pixels(&self.points, painter);
+        for (pos, text) in self.labels.iter() {
+            painter.text(
+                *pos + eframe::egui::vec2(Self::THICKNESS * 2.0, 0.0),
+                Align2::LEFT_BOTTOM,
+                text,
+                FontId::proportional(12.0),
+                Color32::DARK_BLUE,
+            );
+        }
     }
 }
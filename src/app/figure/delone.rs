@@ -125,6 +125,10 @@ impl Delone {
         delone
     }
 
+    pub fn from_triangles(triangles: Vec<Triangle>) -> Self {
+        Self { triangles }
+    }
+
     pub(super) fn get_triangles(&self) -> &[Triangle] {
         &self.triangles
     }
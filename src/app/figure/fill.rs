@@ -0,0 +1,38 @@
+use super::{draw_pixels, Debuggable, Drawable, Figure};
+use crate::pixel::Pixel;
+use eframe::egui::Pos2;
+
+// Pixels of a seed fill, one span per update.
+pub struct Fill {
+    points: Vec<Pixel>,
+    update_func: Box<dyn Iterator<Item = Vec<Pixel>>>,
+    debug_offset: Pos2,
+}
+
+impl Fill {
+    pub fn new(update_func: Box<dyn Iterator<Item = Vec<Pixel>>>, seed: Pos2) -> Self {
+        Self {
+            points: vec![],
+            update_func,
+            debug_offset: seed,
+        }
+    }
+}
+
+impl Figure for Fill {
+    fn as_debug_mut(&mut self) -> Option<&mut dyn Debuggable> {
+        Some(self)
+    }
+
+    fn as_debug(&self) -> Option<&dyn Debuggable> {
+        Some(self)
+    }
+}
+
+impl_debuggable!(Fill, update_func, points, debug_offset);
+
+impl Drawable for Fill {
+    fn draw(&self, painter: &eframe::egui::Painter) {
+        draw_pixels(&self.points, painter);
+    }
+}
@@ -1,4 +1,8 @@
+use crate::curves::{CurvePiece, KnotVector, LineAlgorithm};
 use crate::pixel::Pixel;
+use crate::hull::{Calipers, Hull, HullAlgorithm};
+use crate::polygons::{Clipping, FillRule, FillStyle, Measurements, Triangulation, Validation};
+use crate::raster::{Raster, SeedFillSettings};
 use eframe::egui::{Color32, InputState, Painter, Pos2, Rect, Vec2};
 
 #[macro_use]
@@ -6,6 +10,7 @@ mod macros;
 mod circle;
 mod curve;
 mod ellips;
+mod fill;
 mod hyperbola;
 mod line;
 mod object;
@@ -15,13 +20,14 @@ mod delone;
 mod voronoi;
 
 pub use circle::Circle;
-pub use curve::{Curve, CurveType};
+pub use curve::{Curve, CurveAnalysis, CurveType, NurbsParameters, SplineParameters};
 pub use ellips::Ellips;
+pub use fill::Fill;
 pub use hyperbola::Hyperbola;
 pub use line::Line;
 pub use object::Object;
 pub use parabola::Parabola;
-pub use polygon::Polygon;
+pub use polygon::{Polygon, Walkthrough};
 pub use delone::Delone;
 pub use voronoi::Voronoi;
 pub use delone::Triangle;
@@ -66,20 +72,71 @@ pub trait Figure: Drawable {
     fn as_polygon_transform_mut(&mut self) -> Option<&mut dyn PolygonTransform> {
         None
     }
+
+    fn as_curve_transform(&self) -> Option<&dyn CurveTransform> {
+        None
+    }
+
+    fn as_curve_transform_mut(&mut self) -> Option<&mut dyn CurveTransform> {
+        None
+    }
+}
+
+pub trait CurveTransform: EditableControlPoints {
+    fn selected_point(&self) -> Option<usize>;
+    fn nurbs_mut(&mut self) -> Option<&mut NurbsParameters>;
+    fn spline_mut(&mut self) -> Option<&mut SplineParameters>;
+    fn bspline_mode_mut(&mut self) -> Option<&mut KnotVector>;
+    fn has_continuity(&self) -> bool;
+    fn tolerance_mut(&mut self) -> &mut f32;
+    fn line_algorithm_mut(&mut self) -> &mut LineAlgorithm;
+    fn refresh(&mut self);
+    fn hermite_tangents(&self) -> Option<[Vec2; 2]>;
+    fn set_hermite_tangents(&mut self, tangents: [Vec2; 2]);
+    fn analysis_mut(&mut self) -> &mut CurveAnalysis;
+    fn redraw(&mut self);
+    fn make_circle(&mut self);
+    fn nearest_parameter(&self, pos: Pos2) -> f32;
+    fn split(&self, t: f32) -> Option<(Curve, Curve)>;
+    fn elevate_degree(&mut self) -> bool;
+    fn reverse(&mut self);
+    fn arc_length_table(&self) -> Vec<(f32, f32)>;
+    fn resample(&self, count: usize) -> Vec<Pos2>;
+    fn pieces(&self) -> Vec<CurvePiece>;
+    fn intersect_curve(&mut self, other: &[CurvePiece]) -> usize;
+    fn intersect_segment(&mut self, start: Pos2, end: Pos2) -> usize;
+    fn self_intersect(&mut self) -> usize;
+    fn offset(&self, distance: f32) -> Vec<Curve>;
+    fn stroke_outline(&self, distance: f32) -> Vec<Pos2>;
 }
 
 pub trait PolygonTransform: Selectable {
     fn test_convex(&self) -> bool;
     fn find_internal_normals(&mut self);
-    fn graham(&mut self);
-    fn jarvis(&mut self);
+    fn convex_hull(&mut self, algorithm: HullAlgorithm) -> Hull;
+    fn rotating_calipers(&mut self) -> Option<Calipers>;
     fn test_dot(&self, point: Pos2) -> bool;
     fn test_line(&mut self, start: Pos2, end: Pos2);
     fn first(&mut self);
     fn second(&mut self);
-    fn third(&mut self);
-    fn fourth(&mut self);
+    fn third(&mut self, raster: Raster, seed: Pos2, settings: SeedFillSettings);
+    fn fourth(&mut self, raster: Raster, seed: Pos2, settings: SeedFillSettings);
+    fn antialiased(&mut self);
     fn reset_fill(&mut self);
+    fn add_contours(&mut self, contours: Vec<Vec<Pos2>>);
+    fn vertices(&self) -> &[Pos2];
+    fn contours(&self) -> Vec<Vec<Pos2>>;
+    fn bounds(&self) -> Rect;
+    fn measurements(&self) -> Measurements;
+    fn is_simple(&self) -> bool;
+    fn validate(&mut self) -> Validation;
+    fn fix_winding(&mut self);
+    fn walkthrough_mut(&mut self) -> &mut Option<Walkthrough>;
+    fn show_clipping(&mut self, clipping: &Clipping);
+    fn triangulate(&mut self) -> Option<Triangulation>;
+    fn fill_rule_mut(&mut self) -> &mut FillRule;
+    fn fill_style_mut(&mut self) -> &mut FillStyle;
+    fn show_winding(&mut self);
 }
 
 pub trait Selectable: Figure {
@@ -94,6 +151,16 @@ pub trait EditableControlPoints: Selectable {
     fn hit_test_control_point(&self, pos: Pos2, radius: f32) -> Option<usize>;
     fn move_point(&mut self, pos: Pos2) -> bool;
     fn toggle_point(&mut self, index: usize);
+    fn select_point(&mut self, index: usize);
+
+    // Adds a vertex on the outline under `pos`, if there is one.
+    fn insert_point(&mut self, _pos: Pos2) -> bool {
+        false
+    }
+
+    fn delete_point(&mut self) -> bool {
+        false
+    }
 }
 
 pub trait Debuggable: Figure {
@@ -101,6 +168,11 @@ pub trait Debuggable: Figure {
     fn evaluate(&mut self);
     fn get_offset(&self) -> Pos2;
     fn get_pixels(&self) -> &[Pixel];
+
+    // Text describing the current step, shown in the debug window.
+    fn info(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait Transformable: Selectable {
@@ -1,88 +1,245 @@
-use super::{Drawable, Figure, PolygonTransform, Selectable};
+use super::{Drawable, EditableControlPoints, Figure, PolygonTransform, Selectable};
+use crate::hull::{convex_hull, rotating_calipers, sides, Calipers, Hull, HullAlgorithm};
 use crate::lines;
-use eframe::egui::{Color32, Painter, Pos2, Rect, Shape, Vec2};
+use crate::pixel::Pixel;
+use crate::polygons::{
+    ear_clipping, hatch_lines, is_clockwise, nesting_depths, scanline_spans, validate_contours,
+    winding_number, Clipping, EarTest, FillRule, FillStyle, Measurements, Triangulation,
+    Validation,
+};
+use crate::raster::{coverage, pixel_fill, seed_fill, Raster, SeedFillSettings};
+use eframe::egui::{Align2, Color32, FontId, Painter, Pos2, Rect, Shape, Stroke, Vec2};
 use std::cell::RefCell;
-use std::collections::{HashSet, VecDeque};
+use std::collections::VecDeque;
 
 pub struct Polygon {
     control_points: Vec<Pos2>,
+    holes: Vec<Vec<Pos2>>,
     inner_shapes: RefCell<Vec<Shape>>,
     normals: Vec<Vec2>,
     intercection_points: Vec<Pos2>,
+    warnings: Vec<Pos2>,
+    fill_rule: FillRule,
+    fill_style: FillStyle,
     selected: bool,
     update_func: RefCell<Box<dyn Iterator<Item = Vec<Shape>>>>,
     update_delay: Option<std::time::Duration>,
     update_buffer: RefCell<VecDeque<Shape>>,
     last_update: RefCell<Option<std::time::Instant>>,
+    walkthrough: Option<Walkthrough>,
+    // Contour and vertex index; contour 0 is the outline.
+    selected_point: Option<(usize, usize)>,
+    // Overlays to recompute after the vertices change.
+    validated: bool,
+    calipers: Option<Calipers>,
+    test_lines: Vec<[Pos2; 2]>,
+    last_fill: Option<FillRun>,
+}
+
+#[derive(Clone, Copy)]
+enum FillRun {
+    First,
+    Second,
+    Antialiased,
+}
+
+// Recorded run of an algorithm on the polygon, replayed one step at a time.
+pub struct Walkthrough {
+    pub title: String,
+    pub steps: Vec<Step>,
+    pub current: usize,
+}
+
+pub struct Step {
+    shapes: Vec<Shape>,
+    labels: Vec<(Pos2, String)>,
+    pub note: String,
 }
 
 impl Polygon {
     pub fn new(control_points: Vec<Pos2>) -> Self {
         let new = Self {
             control_points,
+            holes: vec![],
             selected: false,
             normals: vec![],
             inner_shapes: RefCell::new(vec![]),
             intercection_points: vec![],
+            warnings: vec![],
+            fill_rule: FillRule::EvenOdd,
+            fill_style: FillStyle::Solid(Color32::BLACK),
             update_func: RefCell::new(Box::new(std::iter::empty())),
             update_delay: Some(std::time::Duration::from_micros(100)),
             update_buffer: RefCell::new(VecDeque::new()),
             last_update: RefCell::new(None),
+            walkthrough: None,
+            selected_point: None,
+            validated: false,
+            calipers: None,
+            test_lines: vec![],
+            last_fill: None,
         };
         new
     }
 
+    pub fn with_holes(control_points: Vec<Pos2>, holes: Vec<Vec<Pos2>>) -> Self {
+        let mut polygon = Self::new(control_points);
+        polygon.add_contours(holes);
+        polygon
+    }
+
     fn bounding_box(&self) -> (Pos2, Pos2) {
-        self.control_points.iter().fold(
-            (
-                Pos2::new(f32::INFINITY, f32::INFINITY),
-                Pos2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
-            ),
-            |(min, max), p| {
+        self.control_points
+            .iter()
+            .chain(self.holes.iter().flatten())
+            .fold(
                 (
-                    Pos2::new(min.x.min(p.x), min.y.min(p.y)),
-                    Pos2::new(max.x.max(p.x), max.y.max(p.y)),
-                )
-            },
-        )
+                    Pos2::new(f32::INFINITY, f32::INFINITY),
+                    Pos2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+                ),
+                |(min, max), p| {
+                    (
+                        Pos2::new(min.x.min(p.x), min.y.min(p.y)),
+                        Pos2::new(max.x.max(p.x), max.y.max(p.y)),
+                    )
+                },
+            )
     }
 
-    fn is_on_boundary(&self, p: Pos2, eps: f32) -> bool {
-        self.control_points.windows(2).any(|pair| {
-            let (a, b) = (pair[0], pair[1]);
-            distance_to_line_segment(p, a, b) < eps
-        })
+    fn is_filled(&self, point: Pos2) -> bool {
+        self.fill_rule
+            .is_filled(winding_number(&self.contours(), point))
     }
 
-    fn is_inside(&self, point: Pos2) -> bool {
-        let mut crossings = 0;
-        let n = self.control_points.len();
+    // Paints the pixels of a fill read from the canvas in the fill style. The
+    // pixels belong to the canvas as it was when read, so the fill is not
+    // replayed after the vertices change.
+    fn raster_fill(&mut self, fill: Box<dyn Iterator<Item = Vec<Pixel>>>) {
+        self.last_fill = None;
+        let (style, bounds) = (self.fill_style, self.bounds());
+        let func_iter = fill.map(move |pixels| {
+            pixels
+                .iter()
+                .filter_map(|pixel| get_rect_shape(pixel.pos.x, pixel.pos.y, &style, bounds))
+                .collect()
+        });
+        self.update_func = RefCell::new(Box::new(func_iter));
+    }
 
-        for i in 0..n {
-            let p1 = self.control_points[i];
-            let p2 = self.control_points[(i + 1) % n];
+    // Hatching is drawn as whole lines clipped to the polygon, one per step.
+    fn hatch(&mut self, color: Color32, angle: f32, spacing: f32) {
+        let lines = hatch_lines(&self.contours(), self.fill_rule, angle, spacing);
+        let stroke = Stroke::new(1.0, color);
+        let func_iter = lines
+            .into_iter()
+            .map(move |line| vec![Shape::line_segment(line, stroke)]);
+        self.update_func = RefCell::new(Box::new(func_iter));
+    }
 
-            if point.y > p1.y.min(p2.y) && point.y <= p1.y.max(p2.y) {
-                let x_intersection = (point.y - p1.y) * (p2.x - p1.x) / (p2.y - p1.y) + p1.x;
-                if point.x < x_intersection {
-                    crossings += 1;
-                }
+    // Recomputes whatever was shown for the old vertices.
+    fn refresh_overlays(&mut self) {
+        let (lines, fill) = (std::mem::take(&mut self.test_lines), self.last_fill.take());
+        self.reset_fill();
+        self.intercection_points.clear();
+        self.warnings.clear();
+        self.walkthrough = None;
+        if !self.normals.is_empty() {
+            self.find_internal_normals();
+        }
+        if self.validated {
+            self.validate();
+        }
+        if self.calipers.is_some() {
+            self.calipers = self.measure_calipers();
+        }
+        for [start, end] in lines {
+            self.test_line(start, end);
+        }
+        match fill {
+            Some(FillRun::First) => self.first(),
+            Some(FillRun::Second) => self.second(),
+            Some(FillRun::Antialiased) => self.antialiased(),
+            None => (),
+        }
+    }
+
+    // The outline for contour 0, otherwise a hole.
+    fn contour_mut(&mut self, contour: usize) -> &mut Vec<Pos2> {
+        match contour {
+            0 => &mut self.control_points,
+            _ => &mut self.holes[contour - 1],
+        }
+    }
+
+    // Contour and vertex of an index running over all the contours.
+    fn locate(&self, mut index: usize) -> Option<(usize, usize)> {
+        for (contour, points) in self.contours().iter().enumerate() {
+            if index < points.len() {
+                return Some((contour, index));
             }
+            index -= points.len();
         }
+        None
+    }
 
-        crossings % 2 != 0
+    // Winds every contour by how deeply it is nested: outer contours and
+    // islands inside holes run clockwise when `clockwise` is set, holes the
+    // other way. An outermost contour is moved to the front as the outline.
+    fn orient_contours(&mut self, clockwise: bool) {
+        let mut contours = self.contours();
+        let depths = nesting_depths(&contours);
+        for (contour, depth) in contours.iter_mut().zip(&depths) {
+            if is_clockwise(contour) != (clockwise == (depth % 2 == 0)) {
+                contour.reverse();
+            }
+        }
+        if let Some(outer) = depths.iter().position(|depth| *depth == 0) {
+            contours.swap(0, outer);
+        }
+        if contours[0] != self.control_points {
+            self.normals.clear();
+        }
+        self.control_points = contours.remove(0);
+        self.holes = contours;
+    }
+
+    // Non-horizontal edges of every contour for the scanline fills.
+    fn edges(&self) -> Vec<Edge> {
+        let mut edges: Vec<Edge> = Vec::new();
+        for contour in self.contours() {
+            let n = contour.len();
+            for i in 0..n {
+                let (p1, p2) = (contour[i], contour[(i + 1) % n]);
+
+                if p1.y == p2.y {
+                    continue;
+                }
+
+                let direction = if p1.y < p2.y { 1 } else { -1 };
+                let (p1, p2) = if p1.y < p2.y { (p1, p2) } else { (p2, p1) };
+                let slope_inverse = (p2.x - p1.x) / (p2.y - p1.y);
+
+                edges.push(Edge {
+                    y_max: p2.y,
+                    y_min: p1.y,
+                    x_min: p1.x,
+                    slope_inverse,
+                    direction,
+                });
+            }
+        }
+        edges
     }
 
     fn find_intersections(&mut self, start: Pos2, end: Pos2) {
         let mut new_intersections = Vec::new();
-        let n = self.control_points.len();
-
-        for i in 0..n {
-            let j = (i + 1) % n;
-            let start_edge = self.control_points[i];
-            let end_edge = self.control_points[j];
-            if let Some(intersection) = intersect(start, end, start_edge, end_edge) {
-                new_intersections.push(intersection);
+        for contour in self.contours() {
+            let n = contour.len();
+            for i in 0..n {
+                let j = (i + 1) % n;
+                if let Some(intersection) = intersect(start, end, contour[i], contour[j]) {
+                    new_intersections.push(intersection);
+                }
             }
         }
         self.intercection_points.extend(new_intersections);
@@ -121,7 +278,65 @@ impl Polygon {
         }
     }
 
+    fn draw_walkthrough(&self, painter: &Painter) {
+        let Some(step) = self
+            .walkthrough
+            .as_ref()
+            .and_then(|walkthrough| walkthrough.steps.get(walkthrough.current))
+        else {
+            return;
+        };
+        painter.extend(step.shapes.clone());
+        for (pos, text) in &step.labels {
+            painter.text(
+                *pos + Vec2::new(6.0, -6.0),
+                Align2::LEFT_BOTTOM,
+                text,
+                FontId::monospace(12.0),
+                Color32::DARK_GRAY,
+            );
+        }
+    }
+
+    // Calipers around the hull of the outline, which is the outline itself
+    // when it is convex.
+    fn measure_calipers(&self) -> Option<Calipers> {
+        rotating_calipers(&convex_hull(&self.control_points, HullAlgorithm::Andrew).points)
+    }
+
+    fn caliper_shapes(calipers: &Calipers) -> Vec<Shape> {
+        let [start, end] = calipers.diameter;
+        let magenta = Color32::from_rgb(255, 0, 255);
+        vec![
+            Self::outline(&calipers.min_area, Stroke::new(2.0, Color32::GREEN)),
+            Self::outline(&calipers.min_perimeter, Stroke::new(1.0, Color32::GOLD)),
+            Shape::line_segment([start, end], Stroke::new(2.0, Color32::BLUE)),
+            Self::marker(start, Color32::BLUE),
+            Self::marker(end, Color32::BLUE),
+            Shape::line_segment(calipers.width, Stroke::new(2.0, magenta)),
+        ]
+    }
+
+    fn outline(points: &[Pos2], stroke: Stroke) -> Shape {
+        let mut outline = points.to_vec();
+        outline.extend(points.first());
+        Shape::line(outline, stroke)
+    }
+
+    fn triangle_shapes(triangles: &[[Pos2; 3]]) -> Vec<Shape> {
+        let fill = Color32::from_rgba_unmultiplied(255, 255, 255, 90);
+        triangles
+            .iter()
+            .map(|t| Shape::convex_polygon(t.to_vec(), fill, Stroke::new(1.0, Color32::WHITE)))
+            .collect()
+    }
+
+    fn marker(pos: Pos2, color: Color32) -> Shape {
+        Shape::circle_filled(pos, Self::THICKNESS + 1.0, color)
+    }
+
     const THICKNESS: f32 = 2.5;
+    const SCANLINE_STEP: f32 = 5.0;
     const THREASHOLD_HIT: f32 = 3.0;
 }
 
@@ -141,6 +356,14 @@ impl Figure for Polygon {
     fn as_polygon_transform_mut(&mut self) -> Option<&mut dyn PolygonTransform> {
         Some(self)
     }
+
+    fn as_editable_points(&self) -> Option<&dyn EditableControlPoints> {
+        Some(self)
+    }
+
+    fn as_editable_points_mut(&mut self) -> Option<&mut dyn EditableControlPoints> {
+        Some(self)
+    }
 }
 
 impl Selectable for Polygon {
@@ -150,6 +373,7 @@ impl Selectable for Polygon {
 
     fn deselect(&mut self) {
         self.selected = false;
+        self.selected_point = None;
     }
 
     fn hit_test(&self, pos: Pos2) -> bool {
@@ -171,6 +395,15 @@ impl Selectable for Polygon {
             return true;
         }
 
+        if self.holes.iter().any(|hole| {
+            (0..hole.len()).any(|i| {
+                let (p1, p2) = (hole[i], hole[(i + 1) % hole.len()]);
+                distance_to_line_segment(p1, p2, pos) <= Self::THREASHOLD_HIT
+            })
+        }) {
+            return true;
+        }
+
         if self.control_points.len() > 1 {
             let start = self.control_points.last().unwrap();
             let end = self.control_points.first().unwrap();
@@ -248,9 +481,38 @@ impl Drawable for Polygon {
                 },
             );
         }
+        for hole in self.holes.iter().filter(|hole| hole.len() > 1) {
+            let mut outline = hole.clone();
+            outline.push(hole[0]);
+            let color = if self.selected {
+                Color32::from_white_alpha(255)
+            } else {
+                Color32::from_black_alpha(255)
+            };
+            painter.add(Shape::line(outline, Stroke::new(Self::THICKNESS, color)));
+        }
         for pos in self.intercection_points.iter() {
             Self::draw_circle(painter, *pos, (255, 0, 0, 255));
         }
+        for pos in self.warnings.iter() {
+            Self::draw_circle(painter, *pos, (255, 165, 0, 255));
+        }
+        if self.selected {
+            for (contour, points) in self.contours().iter().enumerate() {
+                for (index, pos) in points.iter().enumerate() {
+                    let color = if self.selected_point == Some((contour, index)) {
+                        (50, 50, 50, 255)
+                    } else {
+                        (255, 0, 0, 255)
+                    };
+                    Self::draw_circle(painter, *pos, color);
+                }
+            }
+        }
+        self.draw_walkthrough(painter);
+        if let Some(calipers) = &self.calipers {
+            painter.extend(Self::caliper_shapes(calipers));
+        }
         if self.normals.len() == 0 {
             return;
         }
@@ -276,9 +538,83 @@ impl Drawable for Polygon {
     }
 }
 
+impl EditableControlPoints for Polygon {
+    fn control_points(&self) -> &[Pos2] {
+        &self.control_points
+    }
+
+    fn control_points_mut(&mut self) -> &mut [Pos2] {
+        &mut self.control_points
+    }
+
+    // Indices run over the outline and then the holes.
+    fn hit_test_control_point(&self, pos: Pos2, radius: f32) -> Option<usize> {
+        self.contours()
+            .concat()
+            .iter()
+            .position(|point| point.distance(pos) <= radius)
+    }
+
+    fn move_point(&mut self, pos: Pos2) -> bool {
+        if let Some((contour, index)) = self.selected_point {
+            self.contour_mut(contour)[index] = pos;
+            self.refresh_overlays();
+            return true;
+        }
+        false
+    }
+
+    fn toggle_point(&mut self, index: usize) {
+        let point = self.locate(index);
+        self.selected_point = if self.selected_point == point {
+            None
+        } else {
+            point
+        };
+    }
+
+    fn select_point(&mut self, index: usize) {
+        self.selected_point = self.locate(index);
+    }
+
+    // Splits the edge under `pos`, on any contour, and selects the new vertex.
+    fn insert_point(&mut self, pos: Pos2) -> bool {
+        for (contour, points) in self.contours().iter().enumerate() {
+            let n = points.len();
+            let edge = (0..n).find(|&i| {
+                distance_to_line_segment(points[i], points[(i + 1) % n], pos)
+                    <= Self::THREASHOLD_HIT
+            });
+            if let Some(edge) = edge {
+                self.contour_mut(contour).insert(edge + 1, pos);
+                self.selected_point = Some((contour, edge + 1));
+                self.refresh_overlays();
+                return true;
+            }
+        }
+        false
+    }
+
+    // Every contour keeps at least three vertices.
+    fn delete_point(&mut self) -> bool {
+        let Some((contour, index)) = self.selected_point else {
+            return false;
+        };
+        let points = self.contour_mut(contour);
+        if points.len() <= 3 {
+            return false;
+        }
+        points.remove(index);
+        self.selected_point = None;
+        self.refresh_overlays();
+        true
+    }
+}
+
 impl PolygonTransform for Polygon {
     fn test_convex(&self) -> bool {
-        if self.control_points.len() < 3 {
+        // A hole or a second part is never convex.
+        if self.control_points.len() < 3 || !self.holes.is_empty() {
             return false;
         }
 
@@ -309,7 +645,8 @@ impl PolygonTransform for Polygon {
         let n = self.control_points.len();
         let mut normals = Vec::new();
         let polygon = &self.control_points;
-        let center = find_centroid(polygon);
+        // With y pointing down, (-dy, dx) lies inside a clockwise ring.
+        let sign = if is_clockwise(polygon) { 1.0 } else { -1.0 };
 
         for i in 0..n {
             let a = polygon[i];
@@ -317,142 +654,371 @@ impl PolygonTransform for Polygon {
 
             let edge = b - a;
 
-            let normal = Vec2::new(-edge.y, edge.x).normalized();
-
-            let to_centroid = Vec2::new(center.x - a.x, center.y - a.y);
-            if normal.dot(to_centroid) < 0.0 {
-                normals.push(Vec2::new(-normal.x, -normal.y));
-            } else {
-                normals.push(normal);
-            }
+            normals.push(Vec2::new(-edge.y, edge.x).normalized() * sign);
         }
         self.normals = normals;
     }
 
-    fn graham(&mut self) {
-        if self.control_points.len() < 3 {
-            return;
-        }
-
-        let min_idx = self
-            .control_points
+    // Hull of all the vertices, replayed with the pivot in yellow, the chain
+    // kept so far in green, the point under test in blue and rejected points
+    // in red.
+    fn convex_hull(&mut self, algorithm: HullAlgorithm) -> Hull {
+        let points: Vec<Pos2> = self.contours().concat();
+        let hull = convex_hull(&points, algorithm);
+        let last = hull.steps.len() - 1;
+        let steps = hull
+            .steps
             .iter()
             .enumerate()
-            .min_by(|(_, a), (_, b)| {
-                a.y.partial_cmp(&b.y)
-                    .unwrap()
-                    .then_with(|| a.x.partial_cmp(&b.x).unwrap())
+            .map(|(i, step)| {
+                let mut shapes: Vec<Shape> = step
+                    .groups
+                    .iter()
+                    .map(|group| Self::outline(group, Stroke::new(1.0, Color32::GRAY)))
+                    .collect();
+                shapes.extend(points.iter().map(|p| Self::marker(*p, Color32::GRAY)));
+                shapes.extend(step.rejected.iter().map(|p| Self::marker(*p, Color32::RED)));
+                let stroke = Stroke::new(2.0, Color32::GREEN);
+                if i == last {
+                    shapes.push(Self::outline(&step.chain, stroke));
+                } else {
+                    shapes.push(Shape::line(step.chain.clone(), stroke));
+                }
+                if let (Some(from), Some(candidate)) = (step.chain.last(), step.candidate) {
+                    shapes.push(Shape::line_segment(
+                        [*from, candidate],
+                        Stroke::new(1.0, Color32::BLUE),
+                    ));
+                    shapes.push(Self::marker(candidate, Color32::BLUE));
+                }
+                let mut labels = vec![];
+                if let Some(pivot) = step.pivot {
+                    shapes.push(Self::marker(pivot, Color32::YELLOW));
+                    labels.push((pivot, "P".to_string()));
+                }
+                Step {
+                    shapes,
+                    labels,
+                    note: step.note.clone(),
+                }
             })
-            .map(|(idx, _)| idx)
-            .unwrap();
-
-        let pivot = self.control_points[min_idx];
-
-        let mut sorted_points = self.control_points.clone();
-        sorted_points.sort_by(|a, b| {
-            let cross = cross_product(pivot, *a, *b);
-            if cross > 0.0 {
-                std::cmp::Ordering::Less
-            } else if cross < 0.0 {
-                std::cmp::Ordering::Greater
-            } else {
-                let dist_a = (a.x - pivot.x).hypot(a.y - pivot.y);
-                let dist_b = (b.x - pivot.x).hypot(b.y - pivot.y);
-                dist_a.partial_cmp(&dist_b).unwrap()
-            }
+            .collect();
+        self.walkthrough = Some(Walkthrough {
+            title: hull.algorithm.to_str().to_string(),
+            steps,
+            current: 0,
         });
+        hull
+    }
 
-        let mut hull = Vec::new();
-        for &point in &sorted_points {
-            while hull.len() >= 2 {
-                let last = hull[hull.len() - 1];
-                let second_last = hull[hull.len() - 2];
+    fn test_dot(&self, point: Pos2) -> bool {
+        self.is_filled(point)
+    }
 
-                if cross_product(second_last, last, point) <= 0.0 {
-                    hull.pop();
-                } else {
-                    break;
-                }
-            }
-            hull.push(point);
+    fn test_line(&mut self, start: Pos2, end: Pos2) {
+        {
+            let mut inner_shapes = self.inner_shapes.borrow_mut();
+
+            inner_shapes.extend(lines::dda_line(start, end).flat_map(|vec| {
+                vec.into_iter().map(|pixel| {
+                    Shape::rect_filled(
+                        Rect::from_min_size(pixel.pos, Vec2::new(1.0, 1.0)),
+                        0.0,
+                        Color32::BLACK,
+                    )
+                })
+            }));
         }
+        self.test_lines.push([start, end]);
+        self.find_intersections(start, end);
+    }
 
-        self.control_points = hull;
+    fn vertices(&self) -> &[Pos2] {
+        &self.control_points
     }
 
-    fn jarvis(&mut self) {
-        if self.control_points.len() < 3 {
-            return;
-        }
+    fn contours(&self) -> Vec<Vec<Pos2>> {
+        let mut contours = vec![self.control_points.clone()];
+        contours.extend(self.holes.iter().cloned());
+        contours
+    }
 
-        let mut hull = Vec::new();
-        let n = self.control_points.len();
+    fn bounds(&self) -> Rect {
+        let (min, max) = self.bounding_box();
+        Rect::from_min_max(min, max)
+    }
 
-        let mut leftmost_idx = 0;
-        for i in 1..n {
-            if self.control_points[i].x < self.control_points[leftmost_idx].x
-                || (self.control_points[i].x == self.control_points[leftmost_idx].x
-                    && self.control_points[i].y < self.control_points[leftmost_idx].y)
-            {
-                leftmost_idx = i;
-            }
-        }
+    fn measurements(&self) -> Measurements {
+        Measurements::new(&self.contours(), self.test_convex())
+    }
 
-        let mut current = leftmost_idx;
+    fn is_simple(&self) -> bool {
+        validate_contours(&self.contours()).is_simple()
+    }
 
-        loop {
-            hull.push(self.control_points[current]);
+    // Marks crossings in red, and repeated or collinear vertices and the
+    // vertices of wrongly wound contours in orange.
+    fn validate(&mut self) -> Validation {
+        self.validated = true;
+        let contours = self.contours();
+        let validation = validate_contours(&contours);
+        let vertices = contours.concat();
+        let misnested = validation.misnested.iter().flat_map(|i| &contours[*i]);
+        self.intercection_points = validation.crossings.iter().map(|c| c.0).collect();
+        self.warnings = validation
+            .duplicates
+            .iter()
+            .chain(&validation.collinear)
+            .map(|i| vertices[*i])
+            .chain(misnested.copied())
+            .collect();
+        validation
+    }
 
-            let mut next = (current + 1) % n;
-            for i in 0..n {
-                let cross = cross_product(
-                    self.control_points[current],
-                    self.control_points[next],
-                    self.control_points[i],
-                );
+    // Counterclockwise on screen for outer contours, clockwise for holes.
+    fn fix_winding(&mut self) {
+        self.orient_contours(false);
+    }
 
-                if cross > 0.0
-    || (cross == 0.0
-        && (self.control_points[i].x - self.control_points[current].x)
-            .hypot(self.control_points[i].y - self.control_points[current].y)
-            > (self.control_points[next].x - self.control_points[current].x).hypot(
-                self.control_points[next].y - self.control_points[current].y,
-            )) {
-                    next = i;
+    fn walkthrough_mut(&mut self) -> &mut Option<Walkthrough> {
+        &mut self.walkthrough
+    }
+
+    // Each clipping stage: the clip edge in blue, the output so far in green
+    // and the crossings marked green where the subject enters the clip
+    // polygon and red where it leaves.
+    fn show_clipping(&mut self, clipping: &Clipping) {
+        let steps = clipping
+            .steps
+            .iter()
+            .map(|step| {
+                let mut shapes: Vec<Shape> = step
+                    .edge
+                    .map(|edge| Shape::line_segment(edge, Stroke::new(5.0, Color32::BLUE)))
+                    .into_iter()
+                    .collect();
+                shapes.extend(
+                    step.polygons
+                        .iter()
+                        .map(|polygon| Self::outline(polygon, Stroke::new(2.5, Color32::GREEN))),
+                );
+                let (mut labels, mut note) = (vec![], step.note.clone());
+                for (i, crossing) in step.crossings.iter().enumerate() {
+                    let (color, direction) = if crossing.entering {
+                        (Color32::DARK_GREEN, "entering")
+                    } else {
+                        (Color32::RED, "leaving")
+                    };
+                    shapes.push(Self::marker(crossing.point, color));
+                    labels.push((crossing.point, format!("I{}", i + 1)));
+                    note += &format!(
+                        "\nI{}: ({:.1}, {:.1}) {}",
+                        i + 1,
+                        crossing.point.x,
+                        crossing.point.y,
+                        direction
+                    );
                 }
-            }
+                Step {
+                    shapes,
+                    labels,
+                    note,
+                }
+            })
+            .collect();
+        self.walkthrough = Some(Walkthrough {
+            title: clipping.algorithm.to_str().to_string(),
+            steps,
+            current: 0,
+        });
+    }
 
-            if next == leftmost_idx {
-                break;
-            }
-            current = next;
+    // Each candidate ear over the triangles cut so far: green when it is
+    // clipped, red with the offending vertex when it is rejected.
+    fn triangulate(&mut self) -> Option<Triangulation> {
+        if !self.holes.is_empty() {
+            return None;
         }
+        let triangulation = ear_clipping(&self.control_points);
+        let mut cut = 0;
+        let mut steps: Vec<Step> = triangulation
+            .steps
+            .iter()
+            .map(|step| {
+                let mut shapes = Self::triangle_shapes(&triangulation.triangles[..cut]);
+                shapes.push(Self::outline(
+                    &step.remaining,
+                    Stroke::new(1.0, Color32::GRAY),
+                ));
+                let [_, vertex, _] = step.candidate;
+                let (color, note) = match step.test {
+                    EarTest::Ear => {
+                        cut += 1;
+                        (Color32::GREEN, "ear, clipped".to_string())
+                    }
+                    EarTest::Collinear => (Color32::YELLOW, "collinear, dropped".to_string()),
+                    EarTest::Reflex => (Color32::RED, "rejected: reflex vertex".to_string()),
+                    EarTest::Contains(point) => {
+                        shapes.push(Self::marker(point, Color32::RED));
+                        let note = format!("rejected: contains ({:.1}, {:.1})", point.x, point.y);
+                        (Color32::RED, note)
+                    }
+                };
+                shapes.push(Shape::convex_polygon(
+                    step.candidate.to_vec(),
+                    color.gamma_multiply(0.4),
+                    Stroke::new(2.0, color),
+                ));
+                Step {
+                    shapes,
+                    labels: vec![(vertex, "V".to_string())],
+                    note: format!("({:.1}, {:.1}): {}", vertex.x, vertex.y, note),
+                }
+            })
+            .collect();
+        steps.push(Step {
+            shapes: Self::triangle_shapes(&triangulation.triangles),
+            labels: vec![],
+            note: format!(
+                "{} triangles, total area {:.2}",
+                triangulation.triangles.len(),
+                triangulation.area()
+            ),
+        });
+        self.walkthrough = Some(Walkthrough {
+            title: "Ear clipping".to_string(),
+            steps,
+            current: 0,
+        });
+        Some(triangulation)
+    }
 
-        self.control_points = hull;
+    // One step per hull edge: the calipers lie flush with the edge, the
+    // opposite caliper on the antipodal vertex, with the smallest box so far
+    // kept in green.
+    fn rotating_calipers(&mut self) -> Option<Calipers> {
+        let calipers = self.measure_calipers()?;
+        let mut best: Option<(f32, [Pos2; 4])> = None;
+        let mut steps: Vec<Step> = calipers
+            .steps
+            .iter()
+            .map(|step| {
+                let (a, b) = sides(&step.rectangle);
+                if best.is_none_or(|(area, _)| a * b < area) {
+                    best = Some((a * b, step.rectangle));
+                }
+                let mut shapes = vec![Self::outline(
+                    &step.rectangle,
+                    Stroke::new(1.0, Color32::GRAY),
+                )];
+                if let Some((_, rectangle)) = best {
+                    shapes.push(Self::outline(&rectangle, Stroke::new(1.0, Color32::GREEN)));
+                }
+                shapes.push(Shape::line_segment(
+                    step.edge,
+                    Stroke::new(3.0, Color32::YELLOW),
+                ));
+                shapes.push(Self::marker(step.antipodal, Color32::YELLOW));
+                Step {
+                    shapes,
+                    labels: vec![(step.antipodal, "A".to_string())],
+                    note: step.note.clone(),
+                }
+            })
+            .collect();
+        let (diameter, width) = (
+            calipers.diameter[0].distance(calipers.diameter[1]),
+            calipers.width[0].distance(calipers.width[1]),
+        );
+        let (area, perimeter) = (sides(&calipers.min_area), sides(&calipers.min_perimeter));
+        steps.push(Step {
+            shapes: Self::caliper_shapes(&calipers),
+            labels: vec![],
+            note: format!(
+                "Diameter {:.2}, width {:.2}\nMinimum area {:.2}, minimum perimeter {:.2}",
+                diameter,
+                width,
+                area.0 * area.1,
+                2.0 * (perimeter.0 + perimeter.1)
+            ),
+        });
+        self.walkthrough = Some(Walkthrough {
+            title: "Rotating calipers".to_string(),
+            steps,
+            current: 0,
+        });
+        self.calipers = Some(calipers.clone());
+        Some(calipers)
     }
 
-    fn test_dot(&self, point: Pos2) -> bool {
-        self.is_inside(point)
+    fn fill_rule_mut(&mut self) -> &mut FillRule {
+        &mut self.fill_rule
     }
 
-    fn test_line(&mut self, start: Pos2, end: Pos2) {
-        {
-            let mut inner_shapes = self.inner_shapes.borrow_mut();
+    fn fill_style_mut(&mut self) -> &mut FillStyle {
+        &mut self.fill_style
+    }
 
-            inner_shapes.extend(lines::dda_line(start, end).flat_map(|vec| {
-                vec.into_iter().map(|pixel| {
-                    Shape::rect_filled(
-                        Rect::from_min_size(pixel.pos, Vec2::new(1.0, 1.0)),
-                        0.0,
-                        Color32::BLACK,
-                    )
-                })
-            }));
+    // Every few scanlines: the spans between crossings labelled with their
+    // winding number, green where the fill rule fills them and red where it
+    // leaves them empty.
+    fn show_winding(&mut self) {
+        let (min, max) = self.bounding_box();
+        let contours = self.contours();
+        let mut steps = vec![];
+        let mut y = min.y.floor() + 0.5;
+        while y < max.y {
+            let (mut shapes, mut labels) = (vec![], vec![]);
+            let mut note = format!("y = {:.1}", y);
+            shapes.extend(
+                contours
+                    .iter()
+                    .map(|contour| Self::outline(contour, Stroke::new(1.0, Color32::GRAY))),
+            );
+            for (start, end, winding) in scanline_spans(&contours, y) {
+                let filled = self.fill_rule.is_filled(winding);
+                let stroke = if filled {
+                    Stroke::new(4.0, Color32::GREEN)
+                } else {
+                    Stroke::new(2.0, Color32::RED)
+                };
+                let (a, b) = (Pos2::new(start, y), Pos2::new(end, y));
+                shapes.push(Shape::line_segment([a, b], stroke));
+                labels.push((a.lerp(b, 0.5), winding.to_string()));
+                note += &format!(
+                    "\n[{:.1}, {:.1}] winding {}{}",
+                    start,
+                    end,
+                    winding,
+                    if filled { ", filled" } else { "" }
+                );
+            }
+            steps.push(Step {
+                shapes,
+                labels,
+                note,
+            });
+            y += Self::SCANLINE_STEP;
         }
-        self.find_intersections(start, end);
+        self.walkthrough = Some(Walkthrough {
+            title: format!("Winding numbers ({})", self.fill_rule.to_str()),
+            steps,
+            current: 0,
+        });
+    }
+
+    // Contours inside the shape become holes, contours outside it separate
+    // parts; a contour around the whole shape becomes its outline.
+    fn add_contours(&mut self, contours: Vec<Vec<Pos2>>) {
+        self.holes
+            .extend(contours.into_iter().filter(|contour| contour.len() > 2));
+        self.orient_contours(is_clockwise(&self.control_points));
+        self.reset_fill();
     }
 
     fn reset_fill(&mut self) {
+        self.test_lines.clear();
+        self.last_fill = None;
         self.inner_shapes.borrow_mut().clear();
         self.update_buffer.borrow_mut().clear();
         self.update_func = RefCell::new(Box::new(std::iter::empty()));
@@ -462,45 +1028,30 @@ impl PolygonTransform for Polygon {
         if self.control_points.len() < 3 {
             return;
         }
+        self.last_fill = Some(FillRun::First);
 
-        let mut edges: Vec<Edge> = Vec::new();
-        let n = self.control_points.len();
-
-        for i in 0..n {
-            let p1 = self.control_points[i];
-            let p2 = self.control_points[(i + 1) % n];
-
-            if p1.y == p2.y {
-                continue;
-            }
-
-            let (p1, p2) = if p1.y < p2.y { (p1, p2) } else { (p2, p1) };
-            let slope_inverse = (p2.x - p1.x) / (p2.y - p1.y);
-
-            edges.push(Edge {
-                y_max: p2.y,
-                y_min: p1.y,
-                x_min: p1.x,
-                slope_inverse,
-            });
+        if let FillStyle::Hatch {
+            color,
+            angle,
+            spacing,
+        } = self.fill_style
+        {
+            self.hatch(color, angle, spacing);
+            return;
         }
 
+        let mut edges = self.edges();
+        let (style, bounds) = (self.fill_style, self.bounds());
+
         edges.sort_by(|a, b| {
             a.x_min
                 .partial_cmp(&b.x_min)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        let mut y = self
-            .control_points
-            .iter()
-            .map(|p| p.y)
-            .fold(f32::INFINITY, f32::min); // Find min y
-        let y_max = self
-            .control_points
-            .iter()
-            .map(|p| p.y)
-            .fold(f32::NEG_INFINITY, f32::max); // Find max y
+        let (min, max) = self.bounding_box();
+        let (mut y, y_max) = (min.y, max.y);
+        let fill_rule = self.fill_rule;
 
         let func_iter = std::iter::from_fn(move || {
             if y <= y_max {
@@ -511,19 +1062,21 @@ impl PolygonTransform for Polygon {
                     if edge.y_min <= y && edge.y_max > y {
                         let x_intersection =
                             edge.x_min + (y - edge.y_min) as f32 * edge.slope_inverse;
-                        intersections.push(x_intersection);
+                        intersections.push((x_intersection, edge.direction));
                     }
                 }
 
-                intersections.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                intersections.sort_by(|a, b| a.0.total_cmp(&b.0));
 
-                for i in (0..intersections.len()).step_by(2) {
-                    if i + 1 < intersections.len() {
-                        let x_start = intersections[i].round() as u32;
-                        let x_end = intersections[i + 1].round() as u32;
+                let mut winding = 0;
+                for pair in intersections.windows(2) {
+                    winding += pair[0].1;
+                    if fill_rule.is_filled(winding) {
+                        let x_start = pair[0].0.round() as u32;
+                        let x_end = pair[1].0.round() as u32;
 
                         for x in x_start..=x_end {
-                            buffer.push(get_rect_shape(x as f32, y.floor()));
+                            buffer.extend(get_rect_shape(x as f32, y.floor(), &style, bounds));
                         }
                     }
                 }
@@ -542,29 +1095,21 @@ impl PolygonTransform for Polygon {
         if self.control_points.len() < 3 {
             return;
         }
+        self.last_fill = Some(FillRun::Second);
 
-        let mut edges: Vec<Edge> = Vec::new();
-        let n = self.control_points.len();
-
-        for i in 0..n {
-            let p1 = self.control_points[i];
-            let p2 = self.control_points[(i + 1) % n];
-
-            if p1.y == p2.y {
-                continue;
-            }
-
-            let (p1, p2) = if p1.y < p2.y { (p1, p2) } else { (p2, p1) };
-            let slope_inverse = (p2.x - p1.x) / (p2.y - p1.y);
-
-            edges.push(Edge {
-                y_max: p2.y,
-                y_min: p1.y,
-                x_min: p1.x,
-                slope_inverse,
-            });
+        if let FillStyle::Hatch {
+            color,
+            angle,
+            spacing,
+        } = self.fill_style
+        {
+            self.hatch(color, angle, spacing);
+            return;
         }
 
+        let mut edges = self.edges();
+        let (style, bounds) = (self.fill_style, self.bounds());
+
         edges.sort_by(|a, b| {
             a.y_min
                 .partial_cmp(&b.y_min)
@@ -578,6 +1123,7 @@ impl PolygonTransform for Polygon {
             .map(|e| e.y_max)
             .fold(f32::NEG_INFINITY, f32::max);
         let mut active_edges: Vec<Edge> = Vec::new();
+        let fill_rule = self.fill_rule;
 
         let func_iter = std::iter::from_fn(move || {
             if y <= y_max {
@@ -595,15 +1141,20 @@ impl PolygonTransform for Polygon {
 
                 active_edges.sort_by(|a, b| a.x_min.partial_cmp(&b.x_min).unwrap());
 
-                let intersections: Vec<f32> = active_edges.iter().map(|edge| edge.x_min).collect();
+                let intersections: Vec<(f32, i32)> = active_edges
+                    .iter()
+                    .map(|edge| (edge.x_min, edge.direction))
+                    .collect();
 
-                for i in (0..intersections.len()).step_by(2) {
-                    if i + 1 < intersections.len() {
-                        let x_start = intersections[i].round() as u32;
-                        let x_end = intersections[i + 1].round() as u32;
+                let mut winding = 0;
+                for pair in intersections.windows(2) {
+                    winding += pair[0].1;
+                    if fill_rule.is_filled(winding) {
+                        let x_start = pair[0].0.round() as u32;
+                        let x_end = pair[1].0.round() as u32;
 
                         for x in x_start..=x_end {
-                            buffer.push(get_rect_shape(x as f32, y.floor()));
+                            buffer.extend(get_rect_shape(x as f32, y.floor(), &style, bounds));
                         }
                     }
                 }
@@ -622,139 +1173,46 @@ impl PolygonTransform for Polygon {
         self.update_func = RefCell::new(Box::new(func_iter));
     }
 
-    fn third(&mut self) {
-        let mut visited = HashSet::new();
-        let step = 0.000001;
-        let start = find_centroid(&self.control_points);
-        let sx = start.x as i32;
-        let sy = start.y as i32;
-        let mut stack = vec![(sx, sy)];
-        let polygon = self.control_points.clone();
+    // One row per step, each pixel painted with the fraction of it the
+    // polygon covers.
+    fn antialiased(&mut self) {
+        self.last_fill = Some(FillRun::Antialiased);
+        let coverage = coverage(&self.contours(), self.fill_rule);
+        let (style, bounds) = (self.fill_style, self.bounds());
+        let mut y = 0;
         let func_iter = std::iter::from_fn(move || {
-            loop {
-                if let Some((x, y)) = stack.pop() {
-                    if visited.contains(&(x, y)) {
-                        continue;
-                    }
-
-                    let p = Pos2 {
-                        x: x as f32,
-                        y: y as f32,
-                    };
-                    if is_on_boundary(&polygon, p, step / 2.0) || !is_inside(&polygon, p) {
-                        continue;
-                    }
-
-                    visited.insert((x, y));
-
-                    stack.push((x + 1, y));
-                    stack.push((x - 1, y));
-                    stack.push((x, y + 1));
-                    stack.push((x, y - 1));
-                    return Some(vec![get_rect_shape(p.x, p.y)]);
-                } else {
-                    return None;
-                }
+            if y >= coverage.height {
+                return None;
             }
+            let py = (coverage.origin.1 + y as i32) as f32;
+            let buffer = coverage
+                .row(y)
+                .iter()
+                .enumerate()
+                .filter(|(_, covered)| **covered > 1.0 / 255.0)
+                .filter_map(|(x, covered)| {
+                    let px = (coverage.origin.0 + x as i32) as f32;
+                    let color = style.color_at(Pos2::new(px + 0.5, py + 0.5), bounds)?;
+                    Some(Shape::rect_filled(
+                        Rect::from_min_size(Pos2::new(px, py), Vec2::new(1.0, 1.0)),
+                        0.0,
+                        color.gamma_multiply(*covered),
+                    ))
+                })
+                .collect();
+            y += 1;
+            Some(buffer)
         });
 
         self.update_func = RefCell::new(Box::new(func_iter));
     }
 
-    fn fourth(&mut self) {
-        let step = 0.000001;
-        let start = find_centroid(&self.control_points);
-        let sx = start.x as i32;
-        let sy = start.y as i32;
-        let mut stack = vec![(sx, sy)];
-        let mut visited = HashSet::new();
-        let polygon = self.control_points.clone();
-        let func_iter = std::iter::from_fn(move || {
-            loop {
-                if let Some((x, y)) = stack.pop() {
-                    if visited.contains(&(x, y)) {
-                        continue;
-                    }
-
-                    let p = Pos2 {
-                        x: x as f32,
-                        y: y as f32,
-                    };
-
-                    if is_on_boundary(&polygon, p, step / 2.0) || !is_inside(&polygon, p) {
-                        continue;
-                    }
-                    let mut buffer = vec![];
-
-                    visited.insert((x, y));
-                    buffer.push(get_rect_shape(p.x, p.y));
-
-                    let mut left = x;
-                    while !is_on_boundary(
-                        &polygon,
-                        Pos2 {
-                            x: (left - 1) as f32,
-                            y: y as f32,
-                        },
-                        step / 2.0,
-                    ) && is_inside(
-                        &polygon,
-                        Pos2 {
-                            x: (left - 1) as f32,
-                            y: y as f32,
-                        },
-                    ) {
-                        left -= 1;
-                        if !visited.contains(&(left, y)) {
-                            visited.insert((left, y));
-                            buffer.push(get_rect_shape(left as f32, y as f32));
-                        }
-                    }
-
-                    let mut right = x;
-                    while !is_on_boundary(
-                        &polygon,
-                        Pos2 {
-                            x: (right + 1) as f32,
-                            y: y as f32,
-                        },
-                        step / 2.0,
-                    ) && is_inside(
-                        &polygon,
-                        Pos2 {
-                            x: (right + 1) as f32,
-                            y: y as f32,
-                        },
-                    ) {
-                        right += 1;
-                        if !visited.contains(&(right, y)) {
-                            visited.insert((right, y));
-                            buffer.push(get_rect_shape(right as f32, y as f32))
-                        }
-                    }
-
-                    let mut add_to_stack = |x, y| {
-                        let p_check = Pos2 {
-                            x: x as f32,
-                            y: y as f32,
-                        };
-                        if !visited.contains(&(x, y)) && is_inside(&polygon, p_check) {
-                            stack.push((x, y));
-                        }
-                    };
+    fn third(&mut self, raster: Raster, seed: Pos2, settings: SeedFillSettings) {
+        self.raster_fill(Box::new(pixel_fill(raster, seed, settings)));
+    }
 
-                    for dy in (-1..=1).step_by(2) {
-                        for dx in left..=right {
-                            add_to_stack(dx, y + dy);
-                        }
-                    }
-                    return Some(buffer);
-                } else {
-                    return None;
-                }
-            }
-        });
-        self.update_func = RefCell::new(Box::new(func_iter));
+    fn fourth(&mut self, raster: Raster, seed: Pos2, settings: SeedFillSettings) {
+        self.raster_fill(Box::new(seed_fill(raster, seed, settings)));
     }
 }
 
@@ -764,6 +1222,8 @@ struct Edge {
     y_min: f32,
     x_min: f32,
     slope_inverse: f32,
+    // +1 for edges running down the screen, -1 for edges running up.
+    direction: i32,
 }
 
 fn distance_to_line_segment(p1: Pos2, p2: Pos2, point: Pos2) -> f32 {
@@ -775,12 +1235,13 @@ fn distance_to_line_segment(p1: Pos2, p2: Pos2, point: Pos2) -> f32 {
     point.distance(closest)
 }
 
-fn get_rect_shape(x: f32, y: f32) -> Shape {
-    Shape::rect_filled(
+fn get_rect_shape(x: f32, y: f32, style: &FillStyle, bounds: Rect) -> Option<Shape> {
+    let color = style.color_at(Pos2::new(x + 0.5, y + 0.5), bounds)?;
+    Some(Shape::rect_filled(
         Rect::from_min_size(Pos2::new(x, y), Vec2::new(1.0, 1.0)),
         0.0,
-        Color32::BLACK,
-    )
+        color,
+    ))
 }
 
 fn draw_pixels(shapes: Vec<Shape>, painter: &Painter) {
@@ -813,42 +1274,3 @@ fn intersect(start: Pos2, end: Pos2, start_edge: Pos2, end_edge: Pos2) -> Option
 fn cross_product(o: Pos2, a: Pos2, b: Pos2) -> f32 {
     (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
 }
-
-fn find_centroid(polygon: &[Pos2]) -> Pos2 {
-    let n = polygon.len();
-    let mut sum_x = 0.0;
-    let mut sum_y = 0.0;
-
-    for point in polygon {
-        sum_x += point.x;
-        sum_y += point.y;
-    }
-
-    Pos2::new(sum_x / n as f32, sum_y / n as f32)
-}
-
-fn is_on_boundary(polygon: &[Pos2], p: Pos2, eps: f32) -> bool {
-    polygon.windows(2).any(|pair| {
-        let (a, b) = (pair[0], pair[1]);
-        distance_to_line_segment(p, a, b) < eps
-    })
-}
-
-fn is_inside(polygon: &[Pos2], point: Pos2) -> bool {
-    let mut crossings = 0;
-    let n = polygon.len();
-
-    for i in 0..n {
-        let p1 = polygon[i];
-        let p2 = polygon[(i + 1) % n];
-
-        if point.y > p1.y.min(p2.y) && point.y <= p1.y.max(p2.y) {
-            let x_intersection = (point.y - p1.y) * (p2.x - p1.x) / (p2.y - p1.y) + p1.x;
-            if point.x < x_intersection {
-                crossings += 1;
-            }
-        }
-    }
-
-    crossings % 2 != 0
-}
@@ -1,3 +1,5 @@
+use crate::polygons::ClipAlgorithm;
+use crate::raster::{Raster, SeedFillSettings, SeedRule};
 use eframe::egui;
 mod debug_window;
 mod figure;
@@ -5,6 +7,7 @@ mod mode_panel;
 mod parameter_dialog;
 mod parameters;
 use click_action::ClickAction;
+use drag_action::DragAction;
 use generate_figure::GenerateFigure;
 use keyboard_action::KeyboardAction;
 use parameter_dialog::FigureParameters;
@@ -42,6 +45,7 @@ impl eframe::App for PaintApp {
                 self.debug.figure_index = None;
             }
             self.update_computation(ctx);
+            self.capture_canvas(ctx);
             self.main_painter(ui);
             self.show_parameter_dialog(ctx);
         });
@@ -53,19 +57,39 @@ impl PaintApp {
     fn main_painter(&mut self, ui: &mut egui::Ui) {
         egui::Frame::group(ui.style()).show(ui, |ui| {
             let rect = ui.available_rect_before_wrap();
-            let response = ui.allocate_rect(rect, egui::Sense::click());
+            let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
 
             let painter = ui.painter_at(rect);
+            self.viewport.canvas = rect;
             if response.clicked() {
                 if let Some(pos) = ui.input(|i| i.pointer.hover_pos()) {
                     self.handle_painter_click(pos);
                 }
             }
+            if response.drag_started() {
+                if let Some(pos) = ui.input(|i| i.pointer.press_origin()) {
+                    self.handle_painter_drag_start(pos);
+                }
+            }
+            if response.dragged() {
+                if let Some(pos) = ui.input(|i| i.pointer.hover_pos()) {
+                    self.handle_painter_drag(pos);
+                }
+            }
+            if response.drag_stopped() {
+                self.handle_painter_drag_stop();
+            }
 
             painter.rect_filled(rect, 0.0, egui::Color32::LIGHT_BLUE);
             for figure in self.drawing.figures.iter() {
                 figure.draw(&painter);
             }
+            if let ParameterState::Freehand(freehand) = &self.drawing.parameters {
+                painter.add(egui::Shape::line(
+                    freehand.stroke.clone(),
+                    egui::Stroke::new(1.0, egui::Color32::DARK_GRAY),
+                ));
+            }
         });
     }
 
@@ -106,7 +130,14 @@ impl PaintApp {
                         figure.handle_keyboard(i);
                     }
                 }
-                if let Mode::None = self.drawing.mode {
+                if let Mode::MoveControlPoints(Some(index), _) = self.drawing.mode {
+                    if i.key_pressed(egui::Key::Delete) {
+                        if let Some(figure) = self.drawing.figures[index].as_editable_points_mut() {
+                            figure.delete_point();
+                        }
+                    }
+                }
+                if let Mode::None | Mode::Debug = self.drawing.mode {
                     if self.drawing.parameters.handle_key(i) {
                         self.start_computing();
                     }
@@ -151,10 +182,11 @@ impl PaintApp {
                     return;
                 }
                 if let Some(index) = self.debug.figure_index {
-                    if let Some(debug_figure) = self.drawing.figures[index].as_debug_mut() {
-                        if !debug_figure.update_frame() {
-                            self.drawing.status = Status::Awaiting;
-                        }
+                    let running = self.drawing.figures[index]
+                        .as_debug_mut()
+                        .is_some_and(|debug_figure| debug_figure.update_frame());
+                    if !running {
+                        self.drawing.status = Status::Awaiting;
                     }
                 }
             }
@@ -162,6 +194,73 @@ impl PaintApp {
         }
     }
 
+    // A seed fill reads the canvas as rendered: after the seed is clicked it
+    // asks for a screenshot and starts once the image arrives.
+    fn capture_canvas(&mut self, ctx: &egui::Context) {
+        if let Mode::PolygonOperations(Some(index), PolygonTest::Seed { .. }) = self.drawing.mode {
+            self.capture_polygon_fill(ctx, index);
+            return;
+        }
+        let ParameterState::SeedFill(fill) = &mut self.drawing.parameters else {
+            return;
+        };
+        if fill.seed.is_none() {
+            return;
+        }
+        if !fill.requested {
+            fill.requested = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(Default::default()));
+            return;
+        }
+        if let Some(image) = screenshot(ctx) {
+            let (canvas, scale) = (self.viewport.canvas, ctx.pixels_per_point());
+            fill.raster = Some(Raster::from_screenshot(&image, canvas, scale));
+            fill.settings = self.viewport.seed_fill;
+            self.start_computing();
+        }
+    }
+
+    // Fills III and IV read the polygon from the canvas: they flood the color
+    // under the seed, within the polygon's bounding box.
+    fn capture_polygon_fill(&mut self, ctx: &egui::Context, index: usize) {
+        let Mode::PolygonOperations(
+            _,
+            PolygonTest::Seed {
+                line_by_line,
+                seed: Some(seed),
+                ref mut requested,
+            },
+        ) = self.drawing.mode
+        else {
+            return;
+        };
+        if !*requested {
+            *requested = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(Default::default()));
+            return;
+        }
+        let Some(image) = screenshot(ctx) else {
+            return;
+        };
+        let figure = self.drawing.figures[index]
+            .as_polygon_transform_mut()
+            .expect("Polygon should be already selected");
+        let bounds = figure.bounds().expand(1.0);
+        let rect = egui::Rect::from_min_max(bounds.min.floor(), bounds.max.ceil())
+            .intersect(self.viewport.canvas);
+        let raster = Raster::from_screenshot(&image, rect, ctx.pixels_per_point());
+        let settings = SeedFillSettings {
+            rule: SeedRule::Flood,
+            ..self.viewport.seed_fill
+        };
+        if line_by_line {
+            figure.fourth(raster, seed, settings);
+        } else {
+            figure.third(raster, seed, settings);
+        }
+        self.drawing.mode = Mode::PolygonOperations(Some(index), PolygonTest::None);
+    }
+
     fn handle_painter_click(&mut self, pos: egui::Pos2) {
         if !matches!(self.drawing.status, Status::Awaiting) {
             return;
@@ -172,31 +271,72 @@ impl PaintApp {
                     self.start_computing();
                 }
             }
-            Mode::MoveControlPoints(ref mut index) => {
-                if let Some(index_inner) = index {
-                    if let Some(editable) =
-                        self.drawing.figures[*index_inner].as_editable_points_mut()
-                    {
-                        if let Some(point_index) = editable.hit_test_control_point(pos, 3.0) {
-                            editable.toggle_point(point_index);
-                        } else {
-                            if !editable.move_point(pos) {
+            Mode::MoveControlPoints(ref mut index, ref mut test) => match test {
+                CurveTest::None => {
+                    if let Some(index_inner) = index {
+                        if let Some(editable) =
+                            self.drawing.figures[*index_inner].as_editable_points_mut()
+                        {
+                            if let Some(point_index) = editable.hit_test_control_point(pos, 3.0) {
+                                editable.toggle_point(point_index);
+                            } else if !editable.insert_point(pos) && !editable.move_point(pos) {
                                 *index = None;
                             }
                         }
-                    }
-                } else {
-                    for (new_index, figure) in self.drawing.figures.iter_mut().enumerate() {
-                        if let Some(target) = figure.as_editable_points_mut() {
-                            if target.hit_test(pos) {
-                                target.select();
-                                *index = Some(new_index);
-                                break;
+                    } else {
+                        for (new_index, figure) in self.drawing.figures.iter_mut().enumerate() {
+                            if let Some(target) = figure.as_editable_points_mut() {
+                                if target.hit_test(pos) {
+                                    target.select();
+                                    *index = Some(new_index);
+                                    break;
+                                }
                             }
                         }
                     }
                 }
-            }
+                CurveTest::Split => {
+                    if let Some(ind) = *index {
+                        let t = self.drawing.figures[ind]
+                            .as_curve_transform()
+                            .expect("Curve should be already selected")
+                            .nearest_parameter(pos);
+                        self.drawing.split_curve(ind, t);
+                    }
+                }
+                CurveTest::Intersect => {
+                    if let Some(ind) = *index {
+                        let other = self.drawing.figures.iter().enumerate().position(
+                            |(other_index, figure)| {
+                                other_index != ind
+                                    && figure
+                                        .as_curve_transform()
+                                        .is_some_and(|curve| curve.hit_test(pos))
+                            },
+                        );
+                        if let Some(count) =
+                            other.and_then(|other| self.drawing.intersect_curves(ind, other))
+                        {
+                            self.viewport.modal_window_text =
+                                format!("Found {} intersections", count);
+                            self.drawing.mode = Mode::MoveControlPoints(Some(ind), CurveTest::None);
+                        }
+                    }
+                }
+                CurveTest::Segment(start_point) => match (*start_point, *index) {
+                    (Some(start_point), Some(ind)) => {
+                        let count = self.drawing.figures[ind]
+                            .as_curve_transform_mut()
+                            .expect("Curve should be already selected")
+                            .intersect_segment(start_point, pos);
+                        self.viewport.modal_window_text = format!("Found {} intersections", count);
+                        self.drawing.mode = Mode::MoveControlPoints(*index, CurveTest::None);
+                    }
+                    _ => {
+                        *start_point = Some(pos);
+                    }
+                },
+            },
             Mode::TransformObject(ref mut index) => {
                 if let None = index {
                     for (new_index, figure) in self.drawing.figures.iter_mut().enumerate() {
@@ -247,6 +387,52 @@ impl PaintApp {
                         self.drawing.mode = Mode::PolygonOperations(*index, PolygonTest::None);
                     }
                 }
+                PolygonTest::Clip(algorithm) => {
+                    if let Some(ind) = *index {
+                        let algorithm = *algorithm;
+                        let Some(other) = self.drawing.polygon_at(pos, ind) else {
+                            return;
+                        };
+                        let convex = self.drawing.figures[other]
+                            .as_polygon_transform()
+                            .is_some_and(|polygon| polygon.test_convex());
+                        self.viewport.modal_window_text =
+                            if algorithm == ClipAlgorithm::SutherlandHodgman && !convex {
+                                "Sutherland–Hodgman needs a convex clip polygon".to_string()
+                            } else {
+                                let count = self.drawing.clip_polygons(ind, other, algorithm);
+                                format!("Clipping produced {} polygons", count.unwrap_or(0))
+                            };
+                        self.drawing.mode = Mode::PolygonOperations(Some(ind), PolygonTest::None);
+                    }
+                }
+                PolygonTest::Boolean(operation) => {
+                    if let Some(ind) = *index {
+                        let operation = *operation;
+                        let Some(other) = self.drawing.polygon_at(pos, ind) else {
+                            return;
+                        };
+                        let count = self.drawing.combine_polygons(ind, other, operation);
+                        self.viewport.modal_window_text =
+                            format!("The result has {} polygons", count.unwrap_or(0));
+                        self.drawing.mode = Mode::PolygonOperations(Some(ind), PolygonTest::None);
+                    }
+                }
+                PolygonTest::Seed { seed, .. } => {
+                    if seed.is_none() {
+                        *seed = Some(pos);
+                    }
+                }
+                PolygonTest::Merge => {
+                    if let Some(ind) = *index {
+                        let Some(other) = self.drawing.polygon_at(pos, ind) else {
+                            return;
+                        };
+                        let merged = self.drawing.merge_polygons(ind, other);
+                        self.debug.figure_index = None;
+                        self.drawing.mode = Mode::PolygonOperations(merged, PolygonTest::None);
+                    }
+                }
                 PolygonTest::Line(start_point) => match (start_point.clone(), *index) {
                     (Some(start_point), Some(ind)) => {
                         let figure = self
@@ -266,6 +452,47 @@ impl PaintApp {
         }
     }
 
+    // Grabs the point under the cursor, so a drag never moves a point
+    // selected earlier elsewhere.
+    fn handle_painter_drag_start(&mut self, pos: egui::Pos2) {
+        if !matches!(self.drawing.status, Status::Awaiting) {
+            return;
+        }
+        if let Mode::MoveControlPoints(Some(index), CurveTest::None) = self.drawing.mode {
+            if let Some(editable) = self.drawing.figures[index].as_editable_points_mut() {
+                if let Some(point_index) = editable.hit_test_control_point(pos, 3.0) {
+                    editable.select_point(point_index);
+                }
+            }
+        }
+    }
+
+    fn handle_painter_drag(&mut self, pos: egui::Pos2) {
+        if !matches!(self.drawing.status, Status::Awaiting) {
+            return;
+        }
+        match self.drawing.mode {
+            Mode::None | Mode::Debug => self.drawing.parameters.handle_drag(pos),
+            // Drags the selected point, or grabs the one under the cursor.
+            Mode::MoveControlPoints(Some(index), CurveTest::None) => {
+                if let Some(editable) = self.drawing.figures[index].as_editable_points_mut() {
+                    if !editable.move_point(pos) {
+                        if let Some(point_index) = editable.hit_test_control_point(pos, 3.0) {
+                            editable.toggle_point(point_index);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_painter_drag_stop(&mut self) {
+        if matches!(self.drawing.mode, Mode::None | Mode::Debug) {
+            self.drawing.parameters.handle_drag_stop();
+        }
+    }
+
     fn start_computing(&mut self) {
         let mut new_figure = self
             .drawing
@@ -291,3 +518,12 @@ impl PaintApp {
         }
     }
 }
+
+fn screenshot(ctx: &egui::Context) -> Option<std::sync::Arc<egui::ColorImage>> {
+    ctx.input(|i| {
+        i.raw.events.iter().find_map(|event| match event {
+            egui::Event::Screenshot { image, .. } => Some(image.clone()),
+            _ => None,
+        })
+    })
+}
@@ -1,6 +1,11 @@
+use super::figure::{CurveTransform, PolygonTransform};
 use super::PaintApp;
-use super::{DrawingState, Mode, ParameterState, PolygonTest, ViewportSettings};
-use eframe::egui::{Ui, Window};
+use super::{CurveTest, DrawingState, Mode, ParameterState, PolygonTest, ViewportSettings};
+use crate::curves::{KnotVector, LineAlgorithm};
+use crate::hull::{sides, HullAlgorithm};
+use crate::polygons::{signed_area, BooleanOperation, ClipAlgorithm, FillRule, FillStyle, Pattern};
+use crate::raster::{Connectivity, SeedRule};
+use eframe::egui::{ComboBox, DragValue, Grid, ScrollArea, Slider, Ui, Window};
 
 fn show_debug(ui: &mut Ui, drawing_state: &mut DrawingState) {
     if ui
@@ -15,30 +20,471 @@ fn show_debug(ui: &mut Ui, drawing_state: &mut DrawingState) {
     }
 }
 
-fn show_curve_panel(ui: &mut Ui, drawing_state: &mut DrawingState) {
+fn show_curve_panel(
+    ui: &mut Ui,
+    drawing_state: &mut DrawingState,
+    viewport: &mut ViewportSettings,
+) {
     ui.horizontal(|ui| {
+        show_debug(ui, drawing_state);
         if ui
             .checkbox(
-                &mut (drawing_state.mode == Mode::MoveControlPoints(None)),
+                &mut (drawing_state.mode == Mode::MoveControlPoints(None, CurveTest::None)),
                 "Move points",
             )
             .clicked()
         {
-            if drawing_state.mode == Mode::MoveControlPoints(None) {
+            if drawing_state.mode == Mode::MoveControlPoints(None, CurveTest::None) {
                 drawing_state.change_mode(Mode::None);
             } else {
-                drawing_state.change_mode(Mode::MoveControlPoints(None));
+                drawing_state.change_mode(Mode::MoveControlPoints(None, CurveTest::None));
             };
         }
+        if let Mode::MoveControlPoints(Some(index), _) = drawing_state.mode {
+            if let Some(figure) = drawing_state.figures[index].as_curve_transform_mut() {
+                if ui
+                    .add(
+                        DragValue::new(figure.tolerance_mut())
+                            .speed(0.05)
+                            .range(0.05..=20.0)
+                            .prefix("Tolerance: "),
+                    )
+                    .changed()
+                {
+                    figure.refresh();
+                }
+                let algorithm = figure.line_algorithm_mut();
+                let previous = *algorithm;
+                ComboBox::from_id_salt("curve_line_algorithm")
+                    .selected_text(algorithm.to_str())
+                    .show_ui(ui, |ui| {
+                        for variant in LineAlgorithm::variants() {
+                            ui.selectable_value(algorithm, *variant, variant.to_str());
+                        }
+                    });
+                if previous != *algorithm {
+                    figure.refresh();
+                }
+                show_bspline_mode(ui, figure);
+                show_spline_parameters(ui, figure);
+            }
+        }
+    });
+    show_curve_operations(ui, drawing_state, viewport);
+    if let Mode::MoveControlPoints(Some(index), _) = drawing_state.mode {
+        if let Some(figure) = drawing_state.figures[index].as_curve_transform_mut() {
+            show_curve_analysis(ui, figure);
+            show_hermite_window(ui, figure);
+            show_nurbs_window(ui, figure);
+        }
+    }
+}
+
+fn show_curve_analysis(ui: &mut Ui, figure: &mut dyn CurveTransform) {
+    let analysis = figure.analysis_mut();
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui.checkbox(&mut analysis.tangents, "Tangents").changed();
+        changed |= ui.checkbox(&mut analysis.normals, "Normals").changed();
+        changed |= ui.checkbox(&mut analysis.comb, "Curvature comb").changed();
+        changed |= ui
+            .checkbox(&mut analysis.features, "Inflections and extrema")
+            .changed();
+        changed |= ui
+            .add(
+                DragValue::new(&mut analysis.comb_scale)
+                    .speed(10.0)
+                    .range(1.0..=100000.0)
+                    .prefix("Comb scale: "),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                DragValue::new(&mut analysis.samples)
+                    .range(2..=1000)
+                    .prefix("Samples: "),
+            )
+            .changed();
     });
+    if changed {
+        figure.redraw();
+    }
 }
 
-fn show_polygon_panel(
+fn show_curve_operations(
     ui: &mut Ui,
     drawing_state: &mut DrawingState,
     viewport: &mut ViewportSettings,
 ) {
+    let Mode::MoveControlPoints(Some(index), _) = drawing_state.mode else {
+        return;
+    };
+    let (mut split_at, mut resample, mut offset, mut outline) = (None, false, false, false);
     ui.horizontal(|ui| {
+        let Some(figure) = drawing_state.figures[index].as_curve_transform_mut() else {
+            return;
+        };
+        ui.add(
+            DragValue::new(&mut viewport.split_parameter)
+                .speed(0.01)
+                .range(0.0..=1.0)
+                .prefix("t: "),
+        );
+        if ui.button("Split at t").clicked() {
+            split_at = Some(viewport.split_parameter);
+        }
+        if ui.button("Split at point").clicked() {
+            drawing_state.mode = Mode::MoveControlPoints(Some(index), CurveTest::Split);
+        }
+        if ui.button("Intersect curve").clicked() {
+            drawing_state.mode = Mode::MoveControlPoints(Some(index), CurveTest::Intersect);
+        }
+        if ui.button("Intersect segment").clicked() {
+            drawing_state.mode = Mode::MoveControlPoints(Some(index), CurveTest::Segment(None));
+        }
+        if ui.button("Self-intersections").clicked() {
+            let count = figure.self_intersect();
+            viewport.modal_window_text = format!("Found {} self-intersections", count);
+        }
+        if ui.button("Elevate degree").clicked() && !figure.elevate_degree() {
+            viewport.modal_window_text = "Degree elevation works on Bezier curves".to_string();
+        }
+        if ui.button("Reverse").clicked() {
+            figure.reverse();
+        }
+        if ui.button("Arc length").clicked() {
+            let table = figure.arc_length_table();
+            let step = (table.len() - 1) / 10;
+            let mut text = format!("Length: {:.2}\n", table[table.len() - 1].1);
+            for (t, s) in table.iter().step_by(step.max(1)) {
+                text += &format!("t = {:.2}: s = {:.2}\n", t, s);
+            }
+            viewport.modal_window_text = text;
+        }
+        ui.add(
+            DragValue::new(&mut viewport.resample_count)
+                .range(2..=500)
+                .prefix("Points: "),
+        );
+        resample = ui.button("Resample").clicked();
+    });
+    ui.horizontal(|ui| {
+        ui.add(
+            DragValue::new(&mut viewport.offset_distance)
+                .range(0.5..=500.0)
+                .prefix("Distance: "),
+        );
+        offset = ui.button("Offset").clicked();
+        outline = ui.button("Stroke outline").clicked();
+    });
+    if let Some(t) = split_at {
+        drawing_state.split_curve(index, t);
+    }
+    if resample {
+        drawing_state.resample_curve(index, viewport.resample_count);
+    }
+    if offset {
+        drawing_state.offset_curve(index, viewport.offset_distance);
+    }
+    if outline {
+        drawing_state.outline_curve(index, viewport.offset_distance);
+    }
+}
+
+fn show_bspline_mode(ui: &mut Ui, figure: &mut dyn CurveTransform) {
+    let Some(mode) = figure.bspline_mode_mut() else {
+        return;
+    };
+    let previous = *mode;
+    ComboBox::from_id_salt("bspline_mode")
+        .selected_text(mode.to_str())
+        .show_ui(ui, |ui| {
+            for kind in KnotVector::variants() {
+                ui.selectable_value(mode, *kind, kind.to_str());
+            }
+        });
+    if previous != *mode {
+        figure.refresh();
+    }
+}
+
+fn show_spline_parameters(ui: &mut Ui, figure: &mut dyn CurveTransform) {
+    let has_continuity = figure.has_continuity();
+    let Some(spline) = figure.spline_mut() else {
+        return;
+    };
+    let mut changed = ui
+        .add(
+            DragValue::new(&mut spline.tension)
+                .speed(0.01)
+                .range(-1.0..=1.0)
+                .prefix("Tension: "),
+        )
+        .changed();
+    if has_continuity {
+        changed |= ui
+            .add(
+                DragValue::new(&mut spline.continuity)
+                    .speed(0.01)
+                    .range(-1.0..=1.0)
+                    .prefix("Continuity: "),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                DragValue::new(&mut spline.bias)
+                    .speed(0.01)
+                    .range(-1.0..=1.0)
+                    .prefix("Bias: "),
+            )
+            .changed();
+    }
+    if changed {
+        figure.refresh();
+    }
+}
+
+fn show_hermite_window(ui: &mut Ui, figure: &mut dyn CurveTransform) {
+    let Some(mut tangents) = figure.hermite_tangents() else {
+        return;
+    };
+    let mut changed = false;
+
+    Window::new("Hermite tangents").show(ui.ctx(), |ui| {
+        for (i, tangent) in tangents.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("T{}:", i));
+                changed |= ui
+                    .add(DragValue::new(&mut tangent.x).speed(1.0).prefix("x: "))
+                    .changed();
+                changed |= ui
+                    .add(DragValue::new(&mut tangent.y).speed(1.0).prefix("y: "))
+                    .changed();
+            });
+        }
+    });
+
+    if changed {
+        figure.set_hermite_tangents(tangents);
+    }
+}
+
+fn show_nurbs_window(ui: &mut Ui, figure: &mut dyn CurveTransform) {
+    let selected_point = figure.selected_point();
+    let control_count = figure.control_points().len();
+    let Some(nurbs) = figure.nurbs_mut() else {
+        return;
+    };
+    let mut changed = false;
+    let mut make_circle = false;
+
+    Window::new("NURBS").show(ui.ctx(), |ui| {
+        ui.horizontal(|ui| {
+            let max_degree = (control_count - 1).max(1);
+            if ui
+                .add(
+                    DragValue::new(&mut nurbs.degree)
+                        .range(1..=max_degree)
+                        .prefix("Degree: "),
+                )
+                .changed()
+            {
+                nurbs.reset_knots(control_count);
+                changed = true;
+            }
+            let previous = nurbs.knot_vector;
+            ComboBox::from_id_salt("knot_vector")
+                .selected_text(nurbs.knot_vector.to_str())
+                .show_ui(ui, |ui| {
+                    for kind in KnotVector::variants() {
+                        ui.selectable_value(&mut nurbs.knot_vector, *kind, kind.to_str());
+                    }
+                });
+            if previous != nurbs.knot_vector {
+                nurbs.reset_knots(control_count);
+                changed = true;
+            }
+            if ui.button("Circle").clicked() {
+                make_circle = true;
+            }
+        });
+
+        ui.label("Weights:");
+        ui.horizontal_wrapped(|ui| {
+            for (i, weight) in nurbs.weights.iter_mut().enumerate() {
+                let prefix = if selected_point == Some(i) {
+                    format!("[w{}]: ", i)
+                } else {
+                    format!("w{}: ", i)
+                };
+                changed |= ui
+                    .add(
+                        DragValue::new(weight)
+                            .speed(0.02)
+                            .range(0.01..=100.0)
+                            .prefix(prefix),
+                    )
+                    .changed();
+            }
+        });
+
+        ui.label("Knots:");
+        ui.horizontal_wrapped(|ui| {
+            let count = nurbs.knots.len();
+            for i in 0..count {
+                let low = if i == 0 { f32::MIN } else { nurbs.knots[i - 1] };
+                let high = if i + 1 == count {
+                    f32::MAX
+                } else {
+                    nurbs.knots[i + 1]
+                };
+                changed |= ui
+                    .add(
+                        DragValue::new(&mut nurbs.knots[i])
+                            .speed(0.02)
+                            .range(low..=high)
+                            .prefix(format!("u{}: ", i)),
+                    )
+                    .changed();
+            }
+        });
+        if ui.button("Reset knots").clicked() {
+            nurbs.reset_knots(control_count);
+            changed = true;
+        }
+    });
+
+    if make_circle {
+        figure.make_circle();
+    } else if changed {
+        figure.refresh();
+    }
+}
+
+fn show_walkthrough_window(
+    ui: &mut Ui,
+    figure: &mut dyn PolygonTransform,
+    viewport: &mut ViewportSettings,
+) {
+    let slot = figure.walkthrough_mut();
+    let Some(walkthrough) = slot else {
+        return;
+    };
+    let last = walkthrough.steps.len().saturating_sub(1);
+    let mut step = walkthrough.current.min(last);
+    let mut open = true;
+
+    if let Some(previous) = viewport.animation {
+        let interval = std::time::Duration::from_millis(viewport.step_interval);
+        if previous.elapsed() >= interval {
+            step = (step + 1).min(last);
+            viewport.animation = (step < last).then(std::time::Instant::now);
+        }
+    }
+
+    Window::new("Walkthrough")
+        .open(&mut open)
+        .show(ui.ctx(), |ui| {
+            ui.label(&walkthrough.title);
+            ui.horizontal(|ui| {
+                if ui.button("<").clicked() {
+                    step = step.saturating_sub(1);
+                }
+                ui.add(Slider::new(&mut step, 0..=last).text("Step"));
+                if ui.button(">").clicked() {
+                    step = (step + 1).min(last);
+                }
+                let playing = viewport.animation.is_some();
+                if ui.button(if playing { "Pause" } else { "Play" }).clicked() {
+                    if !playing && step == last {
+                        step = 0;
+                    }
+                    viewport.animation = (!playing).then(std::time::Instant::now);
+                }
+                ui.add(
+                    DragValue::new(&mut viewport.step_interval)
+                        .range(10..=5000)
+                        .suffix(" ms"),
+                );
+            });
+            if let Some(current) = walkthrough.steps.get(step) {
+                ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    ui.label(&current.note);
+                });
+            }
+        });
+
+    walkthrough.current = step;
+    if !open {
+        *slot = None;
+        viewport.animation = None;
+    }
+}
+
+fn show_fill_style(ui: &mut Ui, style: &mut FillStyle) {
+    ComboBox::from_id_salt("fill_style")
+        .selected_text(style.to_str())
+        .show_ui(ui, |ui| {
+            for variant in FillStyle::variants() {
+                let current = std::mem::discriminant(style) == std::mem::discriminant(&variant);
+                if ui.selectable_label(current, variant.to_str()).clicked() && !current {
+                    *style = variant;
+                }
+            }
+        });
+    match style {
+        FillStyle::Solid(color) => {
+            ui.color_edit_button_srgba(color);
+        }
+        FillStyle::LinearGradient { from, to, angle } => {
+            ui.color_edit_button_srgba(from);
+            ui.color_edit_button_srgba(to);
+            ui.add(DragValue::new(angle).range(0.0..=360.0).suffix("°"));
+        }
+        FillStyle::RadialGradient { inner, outer } => {
+            ui.color_edit_button_srgba(inner);
+            ui.color_edit_button_srgba(outer);
+        }
+        FillStyle::Hatch {
+            color,
+            angle,
+            spacing,
+        } => {
+            ui.color_edit_button_srgba(color);
+            ui.add(DragValue::new(angle).range(0.0..=180.0).suffix("°"));
+            ui.add(
+                DragValue::new(spacing)
+                    .range(1.0..=100.0)
+                    .prefix("Spacing: "),
+            );
+        }
+        FillStyle::Pattern { pattern, color } => {
+            ComboBox::from_id_salt("fill_pattern")
+                .selected_text(pattern.to_str())
+                .show_ui(ui, |ui| {
+                    for variant in Pattern::variants() {
+                        ui.selectable_value(pattern, *variant, variant.to_str());
+                    }
+                });
+            ui.color_edit_button_srgba(color);
+        }
+    }
+}
+
+fn warn_if_not_simple(figure: &dyn PolygonTransform, viewport: &mut ViewportSettings) {
+    if !figure.is_simple() {
+        viewport.modal_window_text =
+            "The polygon is not simple, the fill may be wrong. Validate it to see why".to_string();
+    }
+}
+
+fn show_polygon_panel(
+    ui: &mut Ui,
+    drawing_state: &mut DrawingState,
+    viewport: &mut ViewportSettings,
+) {
+    let (mut triangulate, mut split, mut hull) = (None, None, None);
+    ui.horizontal_wrapped(|ui| {
         if ui
             .checkbox(
                 &mut (drawing_state.mode == Mode::PolygonOperations(None, PolygonTest::None)),
@@ -52,12 +498,27 @@ fn show_polygon_panel(
                 drawing_state.change_mode(Mode::PolygonOperations(None, PolygonTest::None));
             };
         }
-        if let Mode::PolygonOperations(Some(index), ..) = &mut drawing_state.mode {
-            let figure = drawing_state.figures[*index]
+        if ui
+            .checkbox(
+                &mut (drawing_state.mode == Mode::MoveControlPoints(None, CurveTest::None)),
+                "Move points",
+            )
+            .clicked()
+        {
+            if drawing_state.mode == Mode::MoveControlPoints(None, CurveTest::None) {
+                drawing_state.change_mode(Mode::None);
+            } else {
+                drawing_state.change_mode(Mode::MoveControlPoints(None, CurveTest::None));
+            };
+        }
+        if let Mode::PolygonOperations(Some(index), ..) = drawing_state.mode {
+            let figure = drawing_state.figures[index]
                 .as_polygon_transform_mut()
                 .unwrap();
             if ui.button("Test convex").clicked() {
-                let res = if figure.test_convex() {
+                let res = if !figure.is_simple() {
+                    "The polygon is not simple, validate it first".to_string()
+                } else if figure.test_convex() {
                     "The polygon is convex".to_string()
                 } else {
                     "The polygon is not convex".to_string()
@@ -67,11 +528,43 @@ fn show_polygon_panel(
             if ui.button("Find internal normals").clicked() {
                 figure.find_internal_normals();
             }
-            if ui.button("Graham").clicked() {
-                figure.graham();
+            ComboBox::from_id_salt("hull_algorithm")
+                .selected_text(viewport.hull_algorithm.to_str())
+                .show_ui(ui, |ui| {
+                    for algorithm in HullAlgorithm::variants() {
+                        ui.selectable_value(
+                            &mut viewport.hull_algorithm,
+                            *algorithm,
+                            algorithm.to_str(),
+                        );
+                    }
+                });
+            if ui.button("Convex hull").clicked() {
+                hull = Some(index);
             }
-            if ui.button("Jarvis").clicked() {
-                figure.jarvis();
+            if ui.button("Rotating calipers").clicked() {
+                viewport.modal_window_text = match figure.rotating_calipers() {
+                    Some(calipers) => {
+                        let (area, perimeter) =
+                            (sides(&calipers.min_area), sides(&calipers.min_perimeter));
+                        let mut text = format!(
+                            "Diameter: {:.2}\nWidth: {:.2}\n\
+                             Minimum area rectangle: {:.2} x {:.2}\n\
+                             Minimum perimeter rectangle: {:.2} x {:.2}",
+                            calipers.diameter[0].distance(calipers.diameter[1]),
+                            calipers.width[0].distance(calipers.width[1]),
+                            area.0,
+                            area.1,
+                            perimeter.0,
+                            perimeter.1
+                        );
+                        if !figure.test_convex() {
+                            text += "\nThe polygon is not convex, its hull was used";
+                        }
+                        text
+                    }
+                    None => "Rotating calipers need at least three vertices off a line".to_string(),
+                };
             }
             if ui.button("Test dot").clicked() {
                 if let Mode::PolygonOperations(index, PolygonTest::None) = &mut drawing_state.mode {
@@ -83,22 +576,234 @@ fn show_polygon_panel(
                     drawing_state.mode = Mode::PolygonOperations(*index, PolygonTest::Line(None));
                 }
             }
+            ComboBox::from_id_salt("clip_algorithm")
+                .selected_text(viewport.clip_algorithm.to_str())
+                .show_ui(ui, |ui| {
+                    for algorithm in ClipAlgorithm::variants() {
+                        ui.selectable_value(
+                            &mut viewport.clip_algorithm,
+                            *algorithm,
+                            algorithm.to_str(),
+                        );
+                    }
+                });
+            if ui.button("Clip by polygon").clicked() {
+                if let Mode::PolygonOperations(index, PolygonTest::None) = &mut drawing_state.mode {
+                    drawing_state.mode =
+                        Mode::PolygonOperations(*index, PolygonTest::Clip(viewport.clip_algorithm));
+                }
+            }
+            ComboBox::from_id_salt("boolean_operation")
+                .selected_text(viewport.boolean_operation.to_str())
+                .show_ui(ui, |ui| {
+                    for operation in BooleanOperation::variants() {
+                        ui.selectable_value(
+                            &mut viewport.boolean_operation,
+                            *operation,
+                            operation.to_str(),
+                        );
+                    }
+                });
+            if ui.button("Combine with polygon").clicked() {
+                if let Mode::PolygonOperations(index, PolygonTest::None) = &mut drawing_state.mode {
+                    drawing_state.mode = Mode::PolygonOperations(
+                        *index,
+                        PolygonTest::Boolean(viewport.boolean_operation),
+                    );
+                }
+            }
+            if ui.button("Merge polygon").clicked() {
+                if let Mode::PolygonOperations(index, PolygonTest::None) = &mut drawing_state.mode {
+                    drawing_state.mode = Mode::PolygonOperations(*index, PolygonTest::Merge);
+                }
+            }
+            if ui.button("Validate").clicked() {
+                let validation = figure.validate();
+                viewport.modal_window_text = format!(
+                    "Self-intersections: {}\nDuplicate vertices: {}\nCollinear vertices: {}\n\
+                     Wrongly wound contours: {}\nOrientation: {}\nThe polygon is {}simple",
+                    validation.crossings.len(),
+                    validation.duplicates.len(),
+                    validation.collinear.len(),
+                    validation.misnested.len(),
+                    if validation.clockwise {
+                        "clockwise"
+                    } else {
+                        "counterclockwise"
+                    },
+                    if validation.is_simple() { "" } else { "not " }
+                );
+            }
+            if ui.button("Split at crossings").clicked() {
+                split = Some(index);
+            }
+            if ui.button("Fix winding").clicked() {
+                figure.fix_winding();
+            }
+            let fill_rule = figure.fill_rule_mut();
+            ComboBox::from_id_salt("fill_rule")
+                .selected_text(fill_rule.to_str())
+                .show_ui(ui, |ui| {
+                    for rule in FillRule::variants() {
+                        ui.selectable_value(fill_rule, *rule, rule.to_str());
+                    }
+                });
+            show_fill_style(ui, figure.fill_style_mut());
             if ui.button("I").clicked() {
+                warn_if_not_simple(figure, viewport);
                 figure.first();
             }
             if ui.button("II").clicked() {
+                warn_if_not_simple(figure, viewport);
                 figure.second();
             }
-            if ui.button("III").clicked() {
-                figure.third();
+            for (label, line_by_line) in [("III", false), ("IV", true)] {
+                if ui.button(label).clicked() {
+                    warn_if_not_simple(figure, viewport);
+                    drawing_state.mode = Mode::PolygonOperations(
+                        Some(index),
+                        PolygonTest::Seed {
+                            line_by_line,
+                            seed: None,
+                            requested: false,
+                        },
+                    );
+                }
             }
-            if ui.button("IV").clicked() {
-                figure.fourth();
+            if ui.button("Antialiased").clicked() {
+                warn_if_not_simple(figure, viewport);
+                figure.antialiased();
             }
             if ui.button("reset fill").clicked() {
                 figure.reset_fill();
             }
+            if ui.button("Winding spans").clicked() {
+                figure.show_winding();
+            }
+            if ui.button("Ear clipping").clicked() {
+                triangulate = Some(index);
+            }
+            show_properties_window(ui, figure);
+            show_walkthrough_window(ui, figure, viewport);
+        }
+    });
+    if let Some(index) = hull {
+        if let Some(hull) = drawing_state.hull_polygon(index, viewport.hull_algorithm) {
+            viewport.modal_window_text = format!(
+                "{}: {} hull vertices, {} orientation tests",
+                hull.algorithm.to_str(),
+                hull.points.len(),
+                hull.operations
+            );
+        }
+    }
+    if let Some(index) = split {
+        let count = drawing_state.split_polygon(index).unwrap_or(0);
+        viewport.modal_window_text = format!("Split into {} polygons", count);
+    }
+    if let Some(index) = triangulate {
+        viewport.modal_window_text = match drawing_state.triangulate_polygon(index) {
+            Some(triangulation) => {
+                let polygon = drawing_state.figures[index]
+                    .as_polygon_transform()
+                    .map_or(0.0, |polygon| signed_area(polygon.vertices()).abs());
+                let mut text = format!(
+                    "{} triangles, total area {:.2}, polygon area {:.2}",
+                    triangulation.triangles.len(),
+                    triangulation.area(),
+                    polygon
+                );
+                if !triangulation.complete {
+                    text += "\nNo ear left: the polygon is not simple";
+                }
+                text
+            }
+            None => "Ear clipping works on polygons without holes".to_string(),
+        };
+    }
+}
+
+fn show_properties_window(ui: &mut Ui, figure: &dyn PolygonTransform) {
+    let m = figure.measurements();
+    Window::new("Polygon properties").show(ui.ctx(), |ui| {
+        Grid::new("polygon_properties")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let rows = [
+                    ("Signed area", format!("{:.2}", m.signed_area)),
+                    ("Area", format!("{:.2}", m.area)),
+                    ("Perimeter", format!("{:.2}", m.perimeter)),
+                    (
+                        "Centroid",
+                        format!("({:.1}, {:.1})", m.centroid.x, m.centroid.y),
+                    ),
+                    (
+                        "Orientation",
+                        if m.clockwise {
+                            "clockwise"
+                        } else {
+                            "counterclockwise"
+                        }
+                        .to_string(),
+                    ),
+                    ("Convex", if m.convex { "yes" } else { "no" }.to_string()),
+                    (
+                        "Bounding box",
+                        format!(
+                            "({:.1}, {:.1}) – ({:.1}, {:.1})",
+                            m.bounds.min.x, m.bounds.min.y, m.bounds.max.x, m.bounds.max.y
+                        ),
+                    ),
+                    ("Vertices", m.vertices.to_string()),
+                    ("Holes", m.holes.to_string()),
+                ];
+                for (label, value) in rows {
+                    ui.label(label);
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+fn show_seed_fill_panel(
+    ui: &mut Ui,
+    drawing_state: &mut DrawingState,
+    viewport: &mut ViewportSettings,
+) {
+    let settings = &mut viewport.seed_fill;
+    ui.horizontal_wrapped(|ui| {
+        show_debug(ui, drawing_state);
+        ComboBox::from_id_salt("seed_rule")
+            .selected_text(settings.rule.to_str())
+            .show_ui(ui, |ui| {
+                for rule in SeedRule::variants() {
+                    ui.selectable_value(&mut settings.rule, *rule, rule.to_str());
+                }
+            });
+        ComboBox::from_id_salt("connectivity")
+            .selected_text(settings.connectivity.to_str())
+            .show_ui(ui, |ui| {
+                for connectivity in Connectivity::variants() {
+                    ui.selectable_value(
+                        &mut settings.connectivity,
+                        *connectivity,
+                        connectivity.to_str(),
+                    );
+                }
+            });
+        if settings.rule == SeedRule::Boundary {
+            ui.label("Boundary");
+            ui.color_edit_button_srgba(&mut settings.boundary);
         }
+        ui.label("Fill");
+        ui.color_edit_button_srgba(&mut settings.color);
+        ui.add(
+            DragValue::new(&mut settings.tolerance)
+                .range(0..=255)
+                .prefix("Tolerance: "),
+        );
+        ui.label("Click inside a closed region to fill it");
     });
 }
 
@@ -111,9 +816,12 @@ impl PaintApp {
             | ps::Ellips(..)
             | ps::Parabola(..)
             | ps::Hyperbola(..) => show_debug(ui, &mut self.drawing),
-            ps::Curve(..) => show_curve_panel(ui, &mut self.drawing),
+            ps::Curve(..) | ps::Freehand(..) => {
+                show_curve_panel(ui, &mut self.drawing, &mut self.viewport)
+            }
             ps::Object(..) | ps::Voronoi(..) | ps::Delone(..) => (),
             ps::Polygon(..) => show_polygon_panel(ui, &mut self.drawing, &mut self.viewport),
+            ps::SeedFill(..) => show_seed_fill_panel(ui, &mut self.drawing, &mut self.viewport),
         }
     }
 }
@@ -19,10 +19,12 @@ impl FigureParameters for ParameterState {
             ps::Hyperbola(hyperbola) => hyperbola.show_dialog(ctx),
             ps::Parabola(parabola) => parabola.show_dialog(ctx),
             ps::Curve(..) => false,
+            ps::Freehand(freehand) => freehand.show_dialog(ctx),
             ps::Object(object) => object.show_dialog(ctx),
             ps::Polygon(..) => false,
             ps::Delone(..) => false,
-            ps::Voronoi(..) => false
+            ps::Voronoi(..) => false,
+            ps::SeedFill(..) => false,
         }
     }
 }
@@ -49,6 +51,34 @@ impl FigureParameters for figure_parameters::Ellips {
     }
 }
 
+impl FigureParameters for figure_parameters::Freehand {
+    fn show_dialog(&mut self, ctx: &egui::Context) -> bool {
+        let mut apply_changes = false;
+        if self.finished {
+            egui::Window::new("Freehand Parameters").show(ctx, |ui| {
+                ui.label("Fit the stroke with cubic Bezier segments:");
+                ui.add(
+                    egui::DragValue::new(&mut self.tolerance)
+                        .speed(0.1)
+                        .prefix("Tolerance: ")
+                        .range(0.1..=100.0),
+                );
+                ui.checkbox(&mut self.keep_stroke, "Keep stroke");
+
+                if ui.button("Apply").clicked() {
+                    apply_changes = true;
+                }
+
+                if ui.button("Cancel").clicked() {
+                    self.stroke.clear();
+                    self.finished = false;
+                }
+            });
+        }
+        apply_changes
+    }
+}
+
 impl FigureParameters for figure_parameters::Hyperbola {
     fn show_dialog(&mut self, ctx: &egui::Context) -> bool {
         let mut apply_changes = false;
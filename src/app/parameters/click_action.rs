@@ -16,10 +16,12 @@ impl ClickAction for ParameterState {
             ps::Hyperbola(params) => params.handle_click(pos),
             ps::Parabola(params) => params.handle_click(pos),
             ps::Curve(params) => params.handle_click(pos),
+            ps::Freehand(..) => false,
             ps::Object(params) => params.handle_click(pos),
             ps::Polygon(params) => params.handle_click(pos),
             ps::Delone(params) => params.handle_click(pos),
             ps::Voronoi(params) => params.handle_click(pos),
+            ps::SeedFill(params) => params.handle_click(pos),
         }
     }
 }
@@ -78,7 +80,7 @@ impl ClickAction for figure_parameters::Hyperbola {
 impl ClickAction for figure_parameters::Curve {
     fn handle_click(&mut self, pos: egui::Pos2) -> bool {
         self.control_points.push(pos);
-        self.control_points.len() == 4
+        self.curve_type.point_count() == Some(self.control_points.len())
     }
 }
 
@@ -93,11 +95,22 @@ impl ClickAction for figure_parameters::Object {
 
 impl ClickAction for figure_parameters::Polygon {
     fn handle_click(&mut self, pos: egui::Pos2) -> bool {
+        if self.new_ring && self.points.len() > 2 {
+            self.rings.push(std::mem::take(&mut self.points));
+        }
         self.points.push(pos);
         false
     }
 }
 
+impl ClickAction for figure_parameters::SeedFill {
+    fn handle_click(&mut self, pos: egui::Pos2) -> bool {
+        self.seed = Some(pos);
+        self.requested = false;
+        false
+    }
+}
+
 impl ClickAction for figure_parameters::Delone {
     fn handle_click(&mut self, pos: egui::Pos2) -> bool {
         self.points.push(pos);
@@ -0,0 +1,42 @@
+use super::figure_parameters;
+use super::ParameterState;
+use eframe::egui;
+
+pub trait DragAction {
+    fn handle_drag(&mut self, _pos: egui::Pos2) {}
+    fn handle_drag_stop(&mut self) {}
+}
+
+impl DragAction for ParameterState {
+    fn handle_drag(&mut self, pos: egui::Pos2) {
+        if let ParameterState::Freehand(params) = self {
+            params.handle_drag(pos);
+        }
+    }
+
+    fn handle_drag_stop(&mut self) {
+        if let ParameterState::Freehand(params) = self {
+            params.handle_drag_stop();
+        }
+    }
+}
+
+impl DragAction for figure_parameters::Freehand {
+    fn handle_drag(&mut self, pos: egui::Pos2) {
+        if self.finished {
+            self.stroke.clear();
+            self.finished = false;
+        }
+        self.stroke.push(pos);
+    }
+
+    // A stroke that never left its first point is dropped instead of fitted.
+    fn handle_drag_stop(&mut self) {
+        match self.stroke.first() {
+            Some(first) if self.stroke.iter().any(|p| p.distance(*first) >= 1.0) => {
+                self.finished = true;
+            }
+            _ => self.stroke.clear(),
+        }
+    }
+}
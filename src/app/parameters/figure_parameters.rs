@@ -1,4 +1,5 @@
 use super::super::figure::CurveType;
+use crate::raster::{Raster, SeedFillSettings};
 use eframe::egui;
 #[derive(Clone)]
 pub enum LineType {
@@ -93,6 +94,24 @@ impl Curve {
     }
 }
 
+pub struct Freehand {
+    pub stroke: Vec<egui::Pos2>,
+    pub finished: bool,
+    pub tolerance: f32,
+    pub keep_stroke: bool,
+}
+
+impl Default for Freehand {
+    fn default() -> Self {
+        Self {
+            stroke: vec![],
+            finished: false,
+            tolerance: 4.0,
+            keep_stroke: false,
+        }
+    }
+}
+
 pub struct Object {
     pub start: Option<egui::Pos2>,
     pub file_path: String,
@@ -108,13 +127,38 @@ impl Object {
 }
 
 pub struct Polygon {
-    pub points: Vec<egui::Pos2>
+    pub points: Vec<egui::Pos2>,
+    // Finished rings; the first one is the outline, the rest become holes.
+    pub rings: Vec<Vec<egui::Pos2>>,
+    // Shift is held, so the next click starts a new ring.
+    pub new_ring: bool,
 }
 
 impl Polygon {
     pub fn new() -> Self {
         Self {
-            points: vec![]
+            points: vec![],
+            rings: vec![],
+            new_ring: false,
+        }
+    }
+}
+
+// Waits for a clicked seed, then for a screenshot of the canvas to fill.
+pub struct SeedFill {
+    pub seed: Option<egui::Pos2>,
+    pub requested: bool,
+    pub raster: Option<Raster>,
+    pub settings: SeedFillSettings,
+}
+
+impl SeedFill {
+    pub fn new() -> Self {
+        Self {
+            seed: None,
+            requested: false,
+            raster: None,
+            settings: SeedFillSettings::default(),
         }
     }
 }
@@ -1,12 +1,14 @@
 use super::super::figure::{
-    Circle, Curve, Delone, Ellips, Hyperbola, Line, Object, Parabola, Polygon, Voronoi,
+    Circle, Curve, Delone, Ellips, Fill, Hyperbola, Line, Object, Parabola, Polygon, Voronoi,
 };
 use super::figure_parameters;
 use super::figure_parameters::LineType;
 use super::Figure;
 use super::ParameterState;
+use crate::curves::fit_bezier_path;
 use crate::lines;
 use crate::pixel::Pixel;
+use crate::raster::seed_fill;
 use crate::second_order_lines;
 use eframe::egui::Pos2;
 
@@ -24,10 +26,12 @@ impl GenerateFigure for ParameterState {
             ps::Hyperbola(params) => params.generate_figure(),
             ps::Parabola(params) => params.generate_figure(),
             ps::Curve(params) => params.generate_figure(),
+            ps::Freehand(params) => params.generate_figure(),
             ps::Object(params) => params.generate_figure(),
             ps::Polygon(params) => params.generate_figure(),
             ps::Delone(params) => params.generate_figure(),
             ps::Voronoi(params) => params.generate_figure(),
+            ps::SeedFill(params) => params.generate_figure(),
         }
     }
 }
@@ -149,14 +153,25 @@ impl GenerateFigure for figure_parameters::Curve {
             curve_type,
         } = self;
 
-        let &[first, second, third, fourth] = points.as_slice() else {
+        if points.len() < curve_type.min_points() {
             return None;
-        };
+        }
+
+        Some(Box::new(Curve::new(points.clone(), curve_type.clone())))
+    }
+}
 
-        Some(Box::new(Curve::new(
-            [first, second, third, fourth],
-            curve_type.clone(),
-        )))
+impl GenerateFigure for figure_parameters::Freehand {
+    fn generate_figure(&mut self) -> Option<Box<dyn Figure>> {
+        if !self.finished {
+            return None;
+        }
+        let segments = fit_bezier_path(&self.stroke, self.tolerance);
+        if segments.is_empty() {
+            return None;
+        }
+        let stroke = self.keep_stroke.then(|| self.stroke.clone());
+        Some(Box::new(Curve::fitted(&segments, stroke)))
     }
 }
 
@@ -175,10 +190,23 @@ impl GenerateFigure for figure_parameters::Object {
 
 impl GenerateFigure for figure_parameters::Polygon {
     fn generate_figure(&mut self) -> Option<Box<dyn Figure>> {
+        let mut rings = self.rings.clone();
         if self.points.len() > 1 {
-            return Some(Box::new(Polygon::new(self.points.clone())));
+            rings.push(self.points.clone());
         }
-        None
+        if rings.is_empty() {
+            return None;
+        }
+        let outline = rings.remove(0);
+        Some(Box::new(Polygon::with_holes(outline, rings)))
+    }
+}
+
+impl GenerateFigure for figure_parameters::SeedFill {
+    fn generate_figure(&mut self) -> Option<Box<dyn Figure>> {
+        let (seed, raster) = (self.seed?, self.raster.take()?);
+        let update_func = seed_fill(raster, seed, self.settings);
+        Some(Box::new(Fill::new(Box::new(update_func), seed)))
     }
 }
 
@@ -13,6 +13,7 @@ impl KeyboardAction for ParameterState {
             ps::Polygon(params) => params.handle_key(i),
             ps::Voronoi(params) => params.handle_key(i),
             ps::Delone(params) => params.handle_key(i),
+            ps::Curve(params) => params.handle_key(i),
             _ => false,
         }
     }
@@ -20,7 +21,8 @@ impl KeyboardAction for ParameterState {
 
 impl KeyboardAction for figure_parameters::Polygon {
     fn handle_key(&mut self, i: &egui::InputState) -> bool {
-        if i.key_pressed(egui::Key::Enter) && self.points.len() > 1 {
+        self.new_ring = i.modifiers.shift;
+        if i.key_pressed(egui::Key::Enter) && (self.points.len() > 1 || !self.rings.is_empty()) {
             return true;
         }
         false
@@ -44,3 +46,15 @@ impl KeyboardAction for figure_parameters::Voronoi {
         false
     }
 }
+
+impl KeyboardAction for figure_parameters::Curve {
+    fn handle_key(&mut self, i: &egui::InputState) -> bool {
+        if i.key_pressed(egui::Key::Enter)
+            && self.curve_type.point_count().is_none()
+            && self.control_points.len() >= self.curve_type.min_points()
+        {
+            return true;
+        }
+        false
+    }
+}
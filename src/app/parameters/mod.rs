@@ -1,7 +1,14 @@
-use super::figure::CurveType;
-use super::figure::{Figure, Selectable};
+use super::figure::{Curve, CurveType};
+use super::figure::{Delone, Figure, Polygon, Selectable, Triangle};
+use crate::hull::{Hull, HullAlgorithm};
+use crate::polygons::{
+    boolean, clip_polygon, contains, nesting_depths, signed_area, split_at_crossings,
+    BooleanOperation, ClipAlgorithm, Triangulation,
+};
+use crate::raster::SeedFillSettings;
 use eframe::egui;
 pub(super) mod click_action;
+pub(super) mod drag_action;
 pub(super) mod figure_parameters;
 pub(super) mod generate_figure;
 pub(super) mod keyboard_action;
@@ -13,10 +20,12 @@ pub enum ParameterState {
     Hyperbola(figure_parameters::Hyperbola),
     Parabola(figure_parameters::Parabola),
     Curve(figure_parameters::Curve),
+    Freehand(figure_parameters::Freehand),
     Object(figure_parameters::Object),
     Polygon(figure_parameters::Polygon),
     Delone(figure_parameters::Delone),
-    Voronoi(figure_parameters::Voronoi)
+    Voronoi(figure_parameters::Voronoi),
+    SeedFill(figure_parameters::SeedFill),
 }
 
 impl Default for ParameterState {
@@ -43,16 +52,23 @@ impl From<&Action> for ParameterState {
             DrawEllips => ps::Ellips(fp::Ellips::default()),
             DrawHyperbola => ps::Hyperbola(fp::Hyperbola::default()),
             DrawParabola => ps::Parabola(fp::Parabola::default()),
-            DrawHermite | DrawBezier | DrawBSpline => match figure {
+            DrawHermite | DrawBezier | DrawBSpline | DrawNurbs | DrawCatmullRom | DrawCardinal
+            | DrawKochanekBartels => match figure {
                 DrawHermite => ps::Curve(fp::Curve::new(CurveType::Hermite)),
                 DrawBezier => ps::Curve(fp::Curve::new(CurveType::Bezier)),
                 DrawBSpline => ps::Curve(fp::Curve::new(CurveType::BSpline)),
+                DrawNurbs => ps::Curve(fp::Curve::new(CurveType::Nurbs)),
+                DrawCatmullRom => ps::Curve(fp::Curve::new(CurveType::CatmullRom)),
+                DrawCardinal => ps::Curve(fp::Curve::new(CurveType::Cardinal)),
+                DrawKochanekBartels => ps::Curve(fp::Curve::new(CurveType::KochanekBartels)),
                 _ => unreachable!(),
             },
+            DrawFreehand => ps::Freehand(fp::Freehand::default()),
             LoadObject => ps::Object(fp::Object::new()),
             DrawPolygon => ps::Polygon(fp::Polygon::new()),
             DrawDelone => ps::Delone(fp::Delone::new()),
-            DrawaVoronoi => ps::Voronoi(fp::Voronoi::new())
+            DrawaVoronoi => ps::Voronoi(fp::Voronoi::new()),
+            SeedFill => ps::SeedFill(fp::SeedFill::new()),
         }
     }
 }
@@ -61,6 +77,24 @@ impl From<&Action> for ParameterState {
 pub enum PolygonTest {
     Line(Option<egui::Pos2>),
     Dot,
+    Clip(ClipAlgorithm),
+    Boolean(BooleanOperation),
+    Merge,
+    // Waits for the seed of fill III, or of IV when filling line by line,
+    // then for a screenshot of the canvas to fill.
+    Seed {
+        line_by_line: bool,
+        seed: Option<egui::Pos2>,
+        requested: bool,
+    },
+    None,
+}
+
+#[derive(Clone)]
+pub enum CurveTest {
+    Split,
+    Intersect,
+    Segment(Option<egui::Pos2>),
     None,
 }
 
@@ -68,7 +102,7 @@ pub enum PolygonTest {
 pub enum Mode {
     None,
     Debug,
-    MoveControlPoints(Option<usize>),
+    MoveControlPoints(Option<usize>, CurveTest),
     TransformObject(Option<usize>),
     PolygonOperations(Option<usize>, PolygonTest),
 }
@@ -78,7 +112,7 @@ impl PartialEq for Mode {
         match (self, other) {
             (Mode::None, Mode::None) => true,
             (Mode::Debug, Mode::Debug) => true,
-            (Mode::MoveControlPoints(_), Mode::MoveControlPoints(_)) => true,
+            (Mode::MoveControlPoints(..), Mode::MoveControlPoints(..)) => true,
             (Mode::PolygonOperations(..), Mode::PolygonOperations(..)) => true,
             _ => false,
         }
@@ -88,7 +122,7 @@ impl PartialEq for Mode {
 impl Mode {
     fn change_to(&mut self, figures: &mut Vec<Box<dyn Figure>>, new_mode: Mode) {
         match self {
-            Mode::MoveControlPoints(Some(index))
+            Mode::MoveControlPoints(Some(index), _)
             | Mode::TransformObject(Some(index))
             | Mode::PolygonOperations(Some(index), _) => {
                 if let Some(selectable) = figures[*index].as_selectable_mut() {
@@ -102,7 +136,7 @@ impl Mode {
 
     fn reset(&mut self) {
         *self = match self {
-            Mode::MoveControlPoints(Some(_)) => Mode::MoveControlPoints(None),
+            Mode::MoveControlPoints(Some(_), _) => Mode::MoveControlPoints(None, CurveTest::None),
             Mode::TransformObject(Some(_)) => Mode::TransformObject(None),
             Mode::PolygonOperations(Some(_), _) => Mode::PolygonOperations(None, PolygonTest::None),
             _ => self.clone(),
@@ -138,6 +172,189 @@ impl DrawingState {
     pub fn reset(&mut self) {
         self.mode.reset();
     }
+
+    // Replaces the curve with its first half and appends the second one.
+    pub fn split_curve(&mut self, index: usize, t: f32) {
+        let Some((first, second)) = self.figures[index]
+            .as_curve_transform()
+            .and_then(|curve| curve.split(t))
+        else {
+            return;
+        };
+        self.figures[index] = Box::new(first);
+        self.figures.push(Box::new(second));
+        self.mode = Mode::MoveControlPoints(None, CurveTest::None);
+    }
+
+    // Appends a polyline through equally spaced points along the curve.
+    pub fn resample_curve(&mut self, index: usize, count: usize) {
+        let Some(curve) = self.figures[index].as_curve_transform() else {
+            return;
+        };
+        let points = curve.resample(count);
+        if points.len() >= 2 {
+            self.figures.push(Box::new(Curve::polyline(&points)));
+        }
+    }
+
+    // Appends the offsets of the curve at `index` on both sides.
+    pub fn offset_curve(&mut self, index: usize, distance: f32) {
+        let Some(curve) = self.figures[index].as_curve_transform() else {
+            return;
+        };
+        for offset in curve.offset(distance) {
+            self.figures.push(Box::new(offset));
+        }
+    }
+
+    // Appends the outline of the curve's stroke as a polygon.
+    pub fn outline_curve(&mut self, index: usize, distance: f32) {
+        let Some(curve) = self.figures[index].as_curve_transform() else {
+            return;
+        };
+        let outline = curve.stroke_outline(distance);
+        if outline.len() >= 3 {
+            self.figures.push(Box::new(Polygon::new(outline)));
+        }
+    }
+
+    // Polygon under `pos` other than the one at `except`.
+    pub fn polygon_at(&self, pos: egui::Pos2, except: usize) -> Option<usize> {
+        self.figures.iter().enumerate().position(|(index, figure)| {
+            index != except
+                && figure
+                    .as_polygon_transform()
+                    .is_some_and(|polygon| polygon.hit_test(pos))
+        })
+    }
+
+    // Clips the polygon at `index` by the one at `other`, appends the pieces
+    // and keeps the steps on the subject for inspection.
+    pub fn clip_polygons(
+        &mut self,
+        index: usize,
+        other: usize,
+        algorithm: ClipAlgorithm,
+    ) -> Option<usize> {
+        let clip = self.figures[other]
+            .as_polygon_transform()?
+            .vertices()
+            .to_vec();
+        let subject = self.figures[index].as_polygon_transform_mut()?;
+        let clipping = clip_polygon(subject.vertices(), &clip, algorithm);
+        subject.show_clipping(&clipping);
+        let count = clipping.result.len();
+        for piece in clipping.result {
+            self.figures.push(Box::new(Polygon::new(piece)));
+        }
+        Some(count)
+    }
+
+    // Appends every region of the boolean operation between the polygons at
+    // `index` and `other` as a polygon with holes.
+    pub fn combine_polygons(
+        &mut self,
+        index: usize,
+        other: usize,
+        operation: BooleanOperation,
+    ) -> Option<usize> {
+        let subject = self.figures[index].as_polygon_transform()?.contours();
+        let clip = self.figures[other].as_polygon_transform()?.contours();
+        let regions = boolean(&subject, &clip, operation);
+        let count = regions.len();
+        for (outer, holes) in regions {
+            let polygon = Polygon::with_holes(outer, holes);
+            self.figures.push(Box::new(polygon));
+        }
+        Some(count)
+    }
+
+    // Moves every contour of the polygon at `other` into the one at `index`
+    // and returns the new index of the merged polygon.
+    pub fn merge_polygons(&mut self, index: usize, other: usize) -> Option<usize> {
+        let contours = self.figures[other].as_polygon_transform()?.contours();
+        self.figures[index]
+            .as_polygon_transform_mut()?
+            .add_contours(contours);
+        self.figures.remove(other);
+        Some(if other < index { index - 1 } else { index })
+    }
+
+    // Replaces the polygon at `index` with the simple polygons it splits into
+    // at its self-intersections; separate parts become polygons of their own
+    // and holes stay with the smallest part around them.
+    pub fn split_polygon(&mut self, index: usize) -> Option<usize> {
+        let contours = self.figures[index].as_polygon_transform()?.contours();
+        let depths = nesting_depths(&contours);
+        let (outer, others) = contours.split_first()?;
+        let mut regions = split_at_crossings(outer);
+        let (parts, inner): (Vec<_>, Vec<_>) = others
+            .iter()
+            .zip(&depths[1..])
+            .partition(|(_, depth)| **depth == 0);
+        regions.extend(parts.into_iter().map(|(part, _)| (part.clone(), vec![])));
+        for (contour, _) in inner {
+            let owner = regions
+                .iter_mut()
+                .filter(|(o, _)| contains(o, contour[0]))
+                .min_by(|a, b| signed_area(&a.0).abs().total_cmp(&signed_area(&b.0).abs()));
+            if let Some(region) = owner {
+                region.1.push(contour.clone());
+            }
+        }
+        let count = regions.len();
+        let mut polygons = regions
+            .into_iter()
+            .map(|(outer, holes)| Box::new(Polygon::with_holes(outer, holes)) as Box<dyn Figure>);
+        if let Some(first) = polygons.next() {
+            self.figures[index] = first;
+            self.figures.extend(polygons);
+            self.mode = Mode::PolygonOperations(None, PolygonTest::None);
+        }
+        Some(count)
+    }
+
+    // Appends the ear clipping triangles of the polygon at `index`.
+    pub fn triangulate_polygon(&mut self, index: usize) -> Option<Triangulation> {
+        let triangulation = self.figures[index]
+            .as_polygon_transform_mut()?
+            .triangulate()?;
+        let triangles = triangulation
+            .triangles
+            .iter()
+            .map(|[a, b, c]| Triangle {
+                a: *a,
+                b: *b,
+                c: *c,
+            })
+            .collect();
+        let delone = Delone::from_triangles(triangles);
+        self.figures.push(Box::new(delone));
+        Some(triangulation)
+    }
+
+    // Appends the convex hull of the polygon at `index` as a new polygon,
+    // leaving the input untouched.
+    pub fn hull_polygon(&mut self, index: usize, algorithm: HullAlgorithm) -> Option<Hull> {
+        let hull = self.figures[index]
+            .as_polygon_transform_mut()?
+            .convex_hull(algorithm);
+        if hull.points.len() >= 3 {
+            let polygon = Polygon::new(hull.points.clone());
+            self.figures.push(Box::new(polygon));
+        }
+        Some(hull)
+    }
+
+    // Marks on the curve at `index` where it meets the curve at `other`.
+    pub fn intersect_curves(&mut self, index: usize, other: usize) -> Option<usize> {
+        let pieces = self.figures[other].as_curve_transform()?.pieces();
+        Some(
+            self.figures[index]
+                .as_curve_transform_mut()?
+                .intersect_curve(&pieces),
+        )
+    }
 }
 
 pub struct DebugState {
@@ -169,7 +386,17 @@ impl Default for ExecutionControl {
 pub struct ViewportSettings {
     pub debug_scale: f32,
     pub scroll_offset: egui::Vec2,
-    pub modal_window_text: String
+    pub modal_window_text: String,
+    pub split_parameter: f32,
+    pub resample_count: usize,
+    pub offset_distance: f32,
+    pub clip_algorithm: ClipAlgorithm,
+    pub boolean_operation: BooleanOperation,
+    pub hull_algorithm: HullAlgorithm,
+    pub step_interval: u64,
+    pub animation: Option<std::time::Instant>,
+    pub canvas: egui::Rect,
+    pub seed_fill: SeedFillSettings,
 }
 
 impl Default for ViewportSettings {
@@ -177,7 +404,17 @@ impl Default for ViewportSettings {
         Self {
             debug_scale: 10.0,
             scroll_offset: egui::Vec2::new(0.0, 0.0),
-            modal_window_text: String::new()
+            modal_window_text: String::new(),
+            split_parameter: 0.5,
+            resample_count: 10,
+            offset_distance: 20.0,
+            clip_algorithm: ClipAlgorithm::SutherlandHodgman,
+            boolean_operation: BooleanOperation::Union,
+            hull_algorithm: HullAlgorithm::Graham,
+            step_interval: 400,
+            animation: None,
+            canvas: egui::Rect::NOTHING,
+            seed_fill: SeedFillSettings::default(),
         }
     }
 }
@@ -194,10 +431,16 @@ pub enum Action {
     DrawHermite,
     DrawBezier,
     DrawBSpline,
+    DrawNurbs,
+    DrawCatmullRom,
+    DrawCardinal,
+    DrawKochanekBartels,
+    DrawFreehand,
     LoadObject,
     DrawPolygon,
     DrawDelone,
-    DrawaVoronoi
+    DrawaVoronoi,
+    SeedFill,
 }
 
 impl Action {
@@ -213,10 +456,16 @@ impl Action {
             Action::DrawHermite,
             Action::DrawBezier,
             Action::DrawBSpline,
+            Action::DrawNurbs,
+            Action::DrawCatmullRom,
+            Action::DrawCardinal,
+            Action::DrawKochanekBartels,
+            Action::DrawFreehand,
             Action::LoadObject,
             Action::DrawPolygon,
             Action::DrawDelone,
-            Action::DrawaVoronoi
+            Action::DrawaVoronoi,
+            Action::SeedFill,
         ]
     }
 
@@ -233,10 +482,16 @@ impl Action {
             act::DrawHermite => "Hermite curve",
             act::DrawBezier => "Bezier curve",
             act::DrawBSpline => "B-spline curve",
+            act::DrawNurbs => "NURBS curve",
+            act::DrawCatmullRom => "Catmull-Rom spline",
+            act::DrawCardinal => "Cardinal spline",
+            act::DrawKochanekBartels => "Kochanek-Bartels spline",
+            act::DrawFreehand => "Freehand curve",
             act::LoadObject => "3D object transforms",
             act::DrawPolygon => "Polygons",
             act::DrawaVoronoi => "Voronoi",
-            act::DrawDelone => "Delone"
+            act::DrawDelone => "Delone",
+            act::SeedFill => "Seed fill",
         }
     }
 }
@@ -1,155 +1,1526 @@
+use crate::lines::{bresenham_line, wu_line};
 use crate::pixel::Pixel;
-use eframe::egui::{Pos2, Vec2};
+use eframe::egui::{Pos2, Rect, Vec2};
+use std::collections::HashMap;
 
-const SCALE: f32 = 1.8;
-const MIN_SCALE: f32 = 20.0;
+const MAX_DEPTH: u32 = 16;
+const MIN_SPAN_DEPTH: u32 = 2;
 
-fn matrix_multiply(a: &[[f32; 4]; 4], b: &[f32; 4]) -> [f32; 4] {
-    let mut result = [0.0; 4];
+const HERMITE_MATRIX: [[f32; 4]; 4] = [
+    [2.0, -2.0, 1.0, 1.0],
+    [-3.0, 3.0, -2.0, -1.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [1.0, 0.0, 0.0, 0.0],
+];
+
+const BSPLINE_MATRIX: [[f32; 4]; 4] = [
+    [-1.0 / 6.0, 3.0 / 6.0, -3.0 / 6.0, 1.0 / 6.0],
+    [3.0 / 6.0, -6.0 / 6.0, 3.0 / 6.0, 0.0],
+    [-3.0 / 6.0, 0.0, 3.0 / 6.0, 0.0],
+    [1.0 / 6.0, 4.0 / 6.0, 1.0 / 6.0, 0.0],
+];
+
+// Inverse of the cubic Bezier basis matrix: maps power-basis coefficients
+// back to Bezier control points.
+const BEZIER_INVERSE: [[f32; 4]; 4] = [
+    [0.0, 0.0, 0.0, 1.0],
+    [0.0, 0.0, 1.0 / 3.0, 1.0],
+    [0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0],
+    [1.0, 1.0, 1.0, 1.0],
+];
+
+fn matrix_multiply_4x4_2x4(matrix: &[[f32; 4]; 4], points: &[[f32; 2]; 4]) -> [[f32; 2]; 4] {
+    let mut result = [[0.0; 2]; 4];
     for i in 0..4 {
         for j in 0..4 {
-            result[i] += a[i][j] * b[j];
+            result[i][0] += matrix[i][j] * points[j][0];
+            result[i][1] += matrix[i][j] * points[j][1];
         }
     }
     result
 }
 
-fn multiply_coeffs(coeffs: &[f32; 4], t_vec: &[f32; 4]) -> f32 {
-    let mut result = 0.0;
-    for i in 0..4 {
-        result += coeffs[i] * t_vec[i];
+#[derive(Clone, Debug)]
+pub struct BezierSegment {
+    pub points: Vec<Pos2>,
+}
+
+impl BezierSegment {
+    pub fn new(points: Vec<Pos2>) -> Self {
+        Self { points }
     }
-    result
+
+    // Converts a cubic given by a basis matrix and its geometry vector into
+    // Bezier form, so every curve type shares one subdivision routine.
+    fn from_basis(basis: &[[f32; 4]; 4], geometry: [Pos2; 4]) -> Self {
+        let geometry = geometry.map(|p| [p.x, p.y]);
+        let coeffs = matrix_multiply_4x4_2x4(basis, &geometry);
+        let points = matrix_multiply_4x4_2x4(&BEZIER_INVERSE, &coeffs);
+        Self::new(points.iter().map(|p| Pos2::new(p[0], p[1])).collect())
+    }
+
+    pub fn degree(&self) -> usize {
+        self.points.len() - 1
+    }
+
+    pub fn start(&self) -> Pos2 {
+        self.points[0]
+    }
+
+    pub fn end(&self) -> Pos2 {
+        self.points[self.degree()]
+    }
+
+    pub fn point_at(&self, t: f32) -> Pos2 {
+        let mut points = self.points.clone();
+        for level in 1..points.len() {
+            for i in 0..points.len() - level {
+                points[i] = points[i].lerp(points[i + 1], t);
+            }
+        }
+        points[0]
+    }
+
+    // Degree n+1 control points describing the same curve:
+    // Q_i = i/(n+1) * P_(i-1) + (1 - i/(n+1)) * P_i.
+    pub fn elevate(&self) -> Self {
+        let n = self.degree() + 1;
+        let mut points = Vec::with_capacity(n + 1);
+        points.push(self.start());
+        for i in 1..n {
+            let alpha = i as f32 / n as f32;
+            points.push(self.points[i].lerp(self.points[i - 1], alpha));
+        }
+        points.push(self.end());
+        Self::new(points)
+    }
+
+    // De Casteljau subdivision: the outer edges of the triangle are the
+    // control polygons of both halves.
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let mut points = self.points.clone();
+        let mut left = Vec::with_capacity(points.len());
+        let mut right = Vec::with_capacity(points.len());
+        left.push(points[0]);
+        right.push(points[points.len() - 1]);
+        for level in 1..points.len() {
+            for i in 0..points.len() - level {
+                points[i] = points[i].lerp(points[i + 1], t);
+            }
+            left.push(points[0]);
+            right.push(points[points.len() - 1 - level]);
+        }
+        right.reverse();
+        (Self::new(left), Self::new(right))
+    }
+
+    // Hodograph: the derivative of a degree n Bezier is the degree n - 1
+    // Bezier over n * (P_(i+1) - P_i), kept as positions for reuse.
+    pub fn derivative(&self) -> Self {
+        if self.degree() == 0 {
+            return Self::new(vec![Pos2::ZERO]);
+        }
+        let n = self.degree() as f32;
+        Self::new(
+            self.points
+                .windows(2)
+                .map(|p| ((p[1] - p[0]) * n).to_pos2())
+                .collect(),
+        )
+    }
+
+    pub fn derivatives_at(&self, t: f32) -> Derivatives {
+        let first = self.derivative();
+        let second = first.derivative();
+        (
+            self.point_at(t),
+            first.point_at(t).to_vec2(),
+            second.point_at(t).to_vec2(),
+        )
+    }
+
+    // Largest distance of the inner control points from the chord; by the
+    // convex hull property the curve deviates from the chord no more than this.
+    fn flatness(&self) -> f32 {
+        let (start, end) = (self.start(), self.end());
+        self.points[1..self.degree()]
+            .iter()
+            .map(|p| distance_to_segment(start, end, *p))
+            .fold(0.0, f32::max)
+    }
+}
+
+// Maps a global parameter in [0, 1] onto one of `count` equal-length segment
+// ranges and the local parameter inside it.
+pub fn locate_segment(count: usize, t: f32) -> (usize, f32) {
+    let scaled = t.clamp(0.0, 1.0) * count as f32;
+    let index = (scaled as usize).min(count - 1);
+    (index, scaled - index as f32)
+}
+
+pub fn segments_point(segments: &[BezierSegment], t: f32) -> Pos2 {
+    let (index, local) = locate_segment(segments.len(), t);
+    segments[index].point_at(local)
+}
+
+// Point, first and second derivative with respect to the curve parameter.
+pub type Derivatives = (Pos2, Vec2, Vec2);
+
+// Derivatives with respect to the global parameter: each segment spans
+// 1 / count of it, which scales the k-th derivative by count^k.
+pub fn segments_derivatives(segments: &[BezierSegment], t: f32) -> Derivatives {
+    let (index, local) = locate_segment(segments.len(), t);
+    let scale = segments.len() as f32;
+    let (point, first, second) = segments[index].derivatives_at(local);
+    (point, first * scale, second * scale * scale)
+}
+
+// Hermite input: both endpoints and the tangent vectors at them.
+pub fn hermite_segment(p0: Pos2, t0: Vec2, p1: Pos2, t1: Vec2) -> BezierSegment {
+    BezierSegment::from_basis(&HERMITE_MATRIX, [p0, p1, t0.to_pos2(), t1.to_pos2()])
+}
+
+// Uniform cubic B-spline. Periodic curves wrap the first three points around
+// into a closed loop, open ones only cover the knots where all four basis
+// functions are defined, and clamped ones interpolate the end points.
+pub fn bspline_segments(control_points: &[Pos2], kind: KnotVector) -> Vec<BezierSegment> {
+    let n = control_points.len();
+    if n < 4 {
+        panic!("At least 4 control points required.");
+    }
+
+    if kind == KnotVector::Clamped {
+        let weights = vec![1.0; n];
+        let knots = nurbs_knots(kind, n, 3);
+        return bezier_decomposition(homogeneous(control_points, &weights), &knots, 3)
+            .into_iter()
+            .map(|(points, ..)| {
+                BezierSegment::new(
+                    points
+                        .iter()
+                        .map(|p| Pos2::new(p[0] / p[2], p[1] / p[2]))
+                        .collect(),
+                )
+            })
+            .collect();
+    }
+
+    let mut augmented_points = Vec::with_capacity(n + 3);
+    augmented_points.extend_from_slice(control_points);
+    if kind == KnotVector::Periodic {
+        augmented_points.extend_from_slice(&control_points[..3]);
+    }
+
+    augmented_points
+        .windows(4)
+        .map(|p| BezierSegment::from_basis(&BSPLINE_MATRIX, [p[0], p[1], p[2], p[3]]))
+        .collect()
+}
+
+// Kochanek–Bartels tangents for the segment `p1 -> p2`; Catmull-Rom is the
+// special case with all three parameters at zero and cardinal splines only
+// vary the tension.
+fn kochanek_bartels_tangents(
+    p0: Pos2,
+    p1: Pos2,
+    p2: Pos2,
+    p3: Pos2,
+    tension: f32,
+    continuity: f32,
+    bias: f32,
+) -> (Vec2, Vec2) {
+    let (t, c, b) = (tension, continuity, bias);
+    let outgoing = (p1 - p0) * ((1.0 - t) * (1.0 + b) * (1.0 + c) / 2.0)
+        + (p2 - p1) * ((1.0 - t) * (1.0 - b) * (1.0 - c) / 2.0);
+    let incoming = (p2 - p1) * ((1.0 - t) * (1.0 + b) * (1.0 - c) / 2.0)
+        + (p3 - p2) * ((1.0 - t) * (1.0 - b) * (1.0 + c) / 2.0);
+    (outgoing, incoming)
+}
+
+pub fn kochanek_bartels_segments(
+    control_points: &[Pos2],
+    tension: f32,
+    continuity: f32,
+    bias: f32,
+) -> Vec<BezierSegment> {
+    let n = control_points.len();
+    let point = |i: isize| control_points[i.clamp(0, n as isize - 1) as usize];
+    (0..n as isize - 1)
+        .map(|i| {
+            let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+            let (t0, t1) = kochanek_bartels_tangents(p0, p1, p2, p3, tension, continuity, bias);
+            BezierSegment::from_basis(&HERMITE_MATRIX, [p1, p2, t0.to_pos2(), t1.to_pos2()])
+        })
+        .collect()
+}
+
+fn flatten_segment(segment: &BezierSegment, tolerance: f32, depth: u32, polyline: &mut Vec<Pos2>) {
+    if depth >= MAX_DEPTH || segment.flatness() <= tolerance {
+        polyline.push(segment.end());
+        return;
+    }
+    let (left, right) = segment.split(0.5);
+    flatten_segment(&left, tolerance, depth + 1, polyline);
+    flatten_segment(&right, tolerance, depth + 1, polyline);
+}
+
+pub fn flatten_segments(segments: &[BezierSegment], tolerance: f32) -> Vec<Pos2> {
+    let mut polyline = Vec::new();
+    if let Some(first) = segments.first() {
+        polyline.push(first.start());
+    }
+    for segment in segments {
+        flatten_segment(segment, tolerance, 0, &mut polyline);
+    }
+    polyline
 }
 
-pub fn generate_hermite_curve(p0: Pos2, p1: Pos2, p2: Pos2, p3: Pos2, pixels: &mut Vec<Pixel>) {
-    let t0 = Vec2::new((p1.x - p0.x) * 3.0, (p1.y - p0.y) * 3.0);
-    let t1 = Vec2::new((p3.x - p2.x) * 3.0, (p3.y - p2.y) * 3.0);
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineAlgorithm {
+    Bresenham,
+    Wu,
+}
+
+impl LineAlgorithm {
+    pub fn variants() -> &'static [LineAlgorithm] {
+        &[LineAlgorithm::Bresenham, LineAlgorithm::Wu]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            LineAlgorithm::Bresenham => "Bresenham",
+            LineAlgorithm::Wu => "Wu",
+        }
+    }
+}
 
-    let points = [p0, p1, p2, p3];
-    let max_distance = points
+// Joins the polyline with the chosen line algorithm. Neighbouring segments
+// share their end pixels, so each position is emitted once, keeping the
+// strongest intensity where antialiased segments overlap.
+pub fn rasterize_polyline(polyline: &[Pos2], algorithm: LineAlgorithm, pixels: &mut Vec<Pixel>) {
+    let mut seen: HashMap<(i32, i32), usize> = HashMap::new();
+    for pair in polyline.windows(2) {
+        let segment: Box<dyn Iterator<Item = Vec<Pixel>>> = match algorithm {
+            LineAlgorithm::Bresenham => Box::new(bresenham_line(pair[0], pair[1])),
+            LineAlgorithm::Wu => Box::new(wu_line(pair[0], pair[1])),
+        };
+        for pixel in segment.flatten() {
+            let key = (pixel.pos.x as i32, pixel.pos.y as i32);
+            match seen.get(&key) {
+                Some(&index) => {
+                    let existing = &mut pixels[index];
+                    existing.intensity = existing.intensity.max(pixel.intensity);
+                }
+                None => {
+                    seen.insert(key, pixels.len());
+                    pixels.push(pixel);
+                }
+            }
+        }
+    }
+}
+
+fn distance_to_segment(start: Pos2, end: Pos2, point: Pos2) -> f32 {
+    let v = end - start;
+    let length_sq = v.length_sq();
+    if length_sq == 0.0 {
+        return point.distance(start);
+    }
+    let t = ((point - start).dot(v) / length_sq).clamp(0.0, 1.0);
+    point.distance(start + v * t)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum KnotVector {
+    Open,
+    Clamped,
+    Periodic,
+}
+
+impl KnotVector {
+    pub fn variants() -> &'static [KnotVector] {
+        &[KnotVector::Open, KnotVector::Clamped, KnotVector::Periodic]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            KnotVector::Open => "Open",
+            KnotVector::Clamped => "Clamped",
+            KnotVector::Periodic => "Periodic",
+        }
+    }
+}
+
+pub fn nurbs_knots(kind: KnotVector, control_count: usize, degree: usize) -> Vec<f32> {
+    match kind {
+        KnotVector::Open => (0..control_count + degree + 1).map(|i| i as f32).collect(),
+        KnotVector::Periodic => (0..control_count + 2 * degree + 1)
+            .map(|i| i as f32)
+            .collect(),
+        KnotVector::Clamped => {
+            let spans = control_count - degree;
+            let mut knots = vec![0.0; degree + 1];
+            knots.extend((1..spans).map(|i| i as f32));
+            knots.extend(std::iter::repeat_n(spans as f32, degree + 1));
+            knots
+        }
+    }
+}
+
+fn find_span(u: f32, degree: usize, knots: &[f32], control_count: usize) -> usize {
+    if u >= knots[control_count] {
+        return control_count - 1;
+    }
+    let mut span = degree;
+    while span < control_count - 1 && u >= knots[span + 1] {
+        span += 1;
+    }
+    span
+}
+
+// Cox–de Boor recurrence in its triangular form: returns the `degree + 1`
+// non-zero basis functions N[span - degree..=span] at `u`.
+fn basis_functions(span: usize, u: f32, degree: usize, knots: &[f32]) -> Vec<f32> {
+    let mut n = vec![0.0; degree + 1];
+    let mut left = vec![0.0; degree + 1];
+    let mut right = vec![0.0; degree + 1];
+    n[0] = 1.0;
+    for j in 1..=degree {
+        left[j] = u - knots[span + 1 - j];
+        right[j] = knots[span + j] - u;
+        let mut saved = 0.0;
+        for r in 0..j {
+            let denom = right[r + 1] + left[j - r];
+            let temp = if denom == 0.0 { 0.0 } else { n[r] / denom };
+            n[r] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        n[j] = saved;
+    }
+    n
+}
+
+pub fn nurbs_point(
+    control_points: &[Pos2],
+    weights: &[f32],
+    knots: &[f32],
+    degree: usize,
+    u: f32,
+) -> Pos2 {
+    let span = find_span(u, degree, knots, control_points.len());
+    let basis = basis_functions(span, u, degree, knots);
+
+    let (mut x, mut y, mut w) = (0.0, 0.0, 0.0);
+    for (i, n) in basis.iter().enumerate() {
+        let index = span - degree + i;
+        let weight = n * weights[index];
+        x += weight * control_points[index].x;
+        y += weight * control_points[index].y;
+        w += weight;
+    }
+    Pos2::new(x / w, y / w)
+}
+
+// N'_(i,p) = p * (N_(i,p-1) / (u_(i+p) - u_i) - N_(i+1,p-1) / (u_(i+p+1) - u_(i+1))),
+// where `lower` holds the degree - 1 functions N[span - degree + 1..=span] or
+// their derivatives.
+fn differentiate_basis(lower: &[f32], span: usize, degree: usize, knots: &[f32]) -> Vec<f32> {
+    let ratio = |n: f32, i: usize| {
+        let length = knots[i + degree] - knots[i];
+        if length == 0.0 {
+            0.0
+        } else {
+            n / length
+        }
+    };
+    (0..=degree)
+        .map(|j| {
+            let i = span - degree + j;
+            let left = if j > 0 { ratio(lower[j - 1], i) } else { 0.0 };
+            let right = if j < degree {
+                ratio(lower[j], i + 1)
+            } else {
+                0.0
+            };
+            degree as f32 * (left - right)
+        })
+        .collect()
+}
+
+fn basis_derivatives(span: usize, u: f32, degree: usize, knots: &[f32]) -> [Vec<f32>; 3] {
+    let values = basis_functions(span, u, degree, knots);
+    let zeros = vec![0.0; degree + 1];
+    if degree == 0 {
+        return [values, zeros.clone(), zeros];
+    }
+    let lower = basis_functions(span, u, degree - 1, knots);
+    let first = differentiate_basis(&lower, span, degree, knots);
+    let second = if degree >= 2 {
+        let lowest = basis_functions(span, u, degree - 2, knots);
+        let lower_first = differentiate_basis(&lowest, span, degree - 1, knots);
+        differentiate_basis(&lower_first, span, degree, knots)
+    } else {
+        zeros
+    };
+    [values, first, second]
+}
+
+// Differentiates the homogeneous curve A(u) / W(u) with the quotient rule:
+// C' = (A' - W'C) / W, C'' = (A'' - 2W'C' - W''C) / W.
+pub fn nurbs_derivatives(
+    control_points: &[Pos2],
+    weights: &[f32],
+    knots: &[f32],
+    degree: usize,
+    u: f32,
+) -> Derivatives {
+    let span = find_span(u, degree, knots, control_points.len());
+    let basis = basis_derivatives(span, u, degree, knots);
+
+    let mut a = [Vec2::ZERO; 3];
+    let mut w = [0.0; 3];
+    for (k, functions) in basis.iter().enumerate() {
+        for (i, n) in functions.iter().enumerate() {
+            let index = span - degree + i;
+            let weight = n * weights[index];
+            a[k] += control_points[index].to_vec2() * weight;
+            w[k] += weight;
+        }
+    }
+    let point = a[0] / w[0];
+    let first = (a[1] - point * w[1]) / w[0];
+    let second = (a[2] - first * (2.0 * w[1]) - point * w[2]) / w[0];
+    (point.to_pos2(), first, second)
+}
+
+// Periodic curves repeat the first `degree` control points so the last span
+// joins the first one with full continuity.
+pub fn nurbs_control_net(
+    control_points: &[Pos2],
+    weights: &[f32],
+    degree: usize,
+    kind: KnotVector,
+) -> (Vec<Pos2>, Vec<f32>) {
+    let mut points = control_points.to_vec();
+    let mut net_weights = weights.to_vec();
+    if kind == KnotVector::Periodic {
+        for i in 0..degree {
+            points.push(control_points[i % control_points.len()]);
+            net_weights.push(weights[i % weights.len()]);
+        }
+    }
+    (points, net_weights)
+}
+
+// Rational spans are not covered by the Bezier path, so NURBS are flattened by
+// bisecting each knot span in parameter space, evaluated with Cox–de Boor.
+fn flatten_span(
+    evaluate: &impl Fn(f32) -> Pos2,
+    (u0, p0): (f32, Pos2),
+    (u1, p1): (f32, Pos2),
+    tolerance: f32,
+    depth: u32,
+    polyline: &mut Vec<Pos2>,
+) {
+    let middle = (u0 + u1) / 2.0;
+    let pm = evaluate(middle);
+    if depth >= MAX_DEPTH
+        || (depth >= MIN_SPAN_DEPTH && distance_to_segment(p0, p1, pm) <= tolerance)
+    {
+        polyline.push(p1);
+        return;
+    }
+    let mid = (middle, pm);
+    flatten_span(evaluate, (u0, p0), mid, tolerance, depth + 1, polyline);
+    flatten_span(evaluate, mid, (u1, p1), tolerance, depth + 1, polyline);
+}
+
+pub fn flatten_nurbs(
+    control_points: &[Pos2],
+    weights: &[f32],
+    knots: &[f32],
+    degree: usize,
+    kind: KnotVector,
+    tolerance: f32,
+) -> Vec<Pos2> {
+    let (points, weights) = nurbs_control_net(control_points, weights, degree, kind);
+    if points.len() <= degree || knots.len() != points.len() + degree + 1 {
+        return vec![];
+    }
+
+    let evaluate = |u: f32| nurbs_point(&points, &weights, knots, degree, u);
+    let domain = &knots[degree..=points.len()];
+    let mut polyline = vec![evaluate(domain[0])];
+    for span in domain.windows(2) {
+        if span[1] > span[0] {
+            let start = (span[0], evaluate(span[0]));
+            let end = (span[1], evaluate(span[1]));
+            flatten_span(&evaluate, start, end, tolerance, 0, &mut polyline);
+        }
+    }
+    polyline
+}
+
+pub fn nurbs_domain(knots: &[f32], degree: usize, control_count: usize) -> (f32, f32) {
+    (knots[degree], knots[control_count])
+}
+
+// Boehm's knot insertion on homogeneous control points; the curve shape is
+// unchanged, one more control point and knot are added.
+fn insert_knot(points: &mut Vec<[f32; 3]>, knots: &mut Vec<f32>, degree: usize, u: f32) {
+    let span = find_span(u, degree, knots, points.len());
+    let mut inserted = Vec::with_capacity(points.len() + 1);
+    for i in 0..=points.len() {
+        let point = if i + degree <= span {
+            points[i]
+        } else if i > span {
+            points[i - 1]
+        } else {
+            let alpha = (u - knots[i]) / (knots[i + degree] - knots[i]);
+            let (a, b) = (points[i], points[i - 1]);
+            [
+                alpha * a[0] + (1.0 - alpha) * b[0],
+                alpha * a[1] + (1.0 - alpha) * b[1],
+                alpha * a[2] + (1.0 - alpha) * b[2],
+            ]
+        };
+        inserted.push(point);
+    }
+    *points = inserted;
+    knots.insert(span + 1, u);
+}
+
+pub type NurbsNet = (Vec<Pos2>, Vec<f32>, Vec<f32>);
+
+fn homogeneous(control_points: &[Pos2], weights: &[f32]) -> Vec<[f32; 3]> {
+    control_points
         .iter()
-        .enumerate()
-        .flat_map(|(i, &p1)| points.iter().skip(i + 1).map(move |&p2| p1.distance(p2)))
-        .fold(0.0, f32::max);
-    let steps = (max_distance * SCALE).max(MIN_SCALE) as usize;
-    let hermite_matrix = [
-        [2.0, -2.0, 1.0, 1.0],
-        [-3.0, 3.0, -2.0, -1.0],
-        [0.0, 0.0, 1.0, 0.0],
-        [1.0, 0.0, 0.0, 0.0],
-    ];
+        .zip(weights)
+        .map(|(p, w)| [p.x * w, p.y * w, *w])
+        .collect()
+}
+
+// Splits the curve at `u` by raising the knot multiplicity to the degree;
+// the control point shared by both halves then lies on the curve.
+pub fn split_nurbs(
+    control_points: &[Pos2],
+    weights: &[f32],
+    knots: &[f32],
+    degree: usize,
+    u: f32,
+) -> Option<(NurbsNet, NurbsNet)> {
+    let (start, end) = nurbs_domain(knots, degree, control_points.len());
+    if u <= start || u >= end {
+        return None;
+    }
+
+    let mut points = homogeneous(control_points, weights);
+    let mut knots = knots.to_vec();
+    let multiplicity = knots.iter().filter(|k| **k == u).count();
+    for _ in multiplicity..degree {
+        insert_knot(&mut points, &mut knots, degree, u);
+    }
+
+    let first = knots.iter().position(|k| *k == u)?;
+    let project = |points: &[[f32; 3]]| -> (Vec<Pos2>, Vec<f32>) {
+        points
+            .iter()
+            .map(|p| (Pos2::new(p[0] / p[2], p[1] / p[2]), p[2]))
+            .unzip()
+    };
+    let (left_points, left_weights) = project(&points[..first]);
+    let (right_points, right_weights) = project(&points[first - 1..]);
+    let mut left_knots = knots[..first + degree].to_vec();
+    left_knots.push(u);
+    let mut right_knots = vec![u];
+    right_knots.extend_from_slice(&knots[first..]);
+
+    Some((
+        (left_points, left_weights, left_knots),
+        (right_points, right_weights, right_knots),
+    ))
+}
+
+const INTERSECTION_DEPTH: u32 = 40;
+const SELF_INTERSECTION_DEPTH: u32 = 12;
+const LEAF_SIZE: f32 = 0.05;
+const MERGE_DISTANCE: f32 = 1.0;
+const MERGE_PARAMETER: f32 = 0.05;
+const SAME_PARAMETER: f32 = 0.01;
+const TANGENT_SINE: f32 = 0.05;
+// Overlapping curves would otherwise produce a hit for every leaf.
+const MAX_HITS: usize = 4096;
+
+// Rational Bezier piece in homogeneous coordinates covering [start, end] of
+// the global curve parameter. Polynomial segments have unit weights; with
+// positive weights the curve stays inside the hull of the projected points.
+#[derive(Clone, Debug)]
+pub struct CurvePiece {
+    points: Vec<[f32; 3]>,
+    start: f32,
+    end: f32,
+}
+
+impl CurvePiece {
+    pub fn line(start: Pos2, end: Pos2) -> Self {
+        Self::from_segment(&BezierSegment::new(vec![start, end]), 0.0, 1.0)
+    }
+
+    fn from_segment(segment: &BezierSegment, start: f32, end: f32) -> Self {
+        let weights = vec![1.0; segment.points.len()];
+        Self {
+            points: homogeneous(&segment.points, &weights),
+            start,
+            end,
+        }
+    }
 
-    let x_coeffs = matrix_multiply(&hermite_matrix, &[p0.x, p2.x, t0.x, t1.x]);
-    let y_coeffs = matrix_multiply(&hermite_matrix, &[p0.y, p2.y, t0.y, t1.y]);
+    fn projected(&self) -> Vec<Pos2> {
+        self.points
+            .iter()
+            .map(|p| Pos2::new(p[0] / p[2], p[1] / p[2]))
+            .collect()
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::from_points(&self.projected())
+    }
 
-    for i in 0..=steps {
-        let t = i as f32 / steps as f32;
-        let t_vec = [t * t * t, t * t, t, 1.0];
+    fn split(&self) -> (Self, Self) {
+        let mut points = self.points.clone();
+        let mut left = vec![points[0]];
+        let mut right = vec![points[points.len() - 1]];
+        for level in 1..points.len() {
+            for i in 0..points.len() - level {
+                let (a, b) = (points[i], points[i + 1]);
+                points[i] = [
+                    (a[0] + b[0]) / 2.0,
+                    (a[1] + b[1]) / 2.0,
+                    (a[2] + b[2]) / 2.0,
+                ];
+            }
+            left.push(points[0]);
+            right.push(points[points.len() - 1 - level]);
+        }
+        right.reverse();
+        let middle = (self.start + self.end) / 2.0;
+        (
+            Self {
+                points: left,
+                start: self.start,
+                end: middle,
+            },
+            Self {
+                points: right,
+                start: middle,
+                end: self.end,
+            },
+        )
+    }
 
-        let x = multiply_coeffs(&x_coeffs, &t_vec);
-        let y = multiply_coeffs(&y_coeffs, &t_vec);
+    fn chord(&self) -> Vec2 {
+        let projected = self.projected();
+        projected[projected.len() - 1] - projected[0]
+    }
 
-        pixels.push(Pixel::new_black(x, y, 255));
+    // The derivative is a positive combination of control polygon edges, so
+    // if every edge advances along one direction the piece cannot loop back
+    // onto itself.
+    fn is_monotone(&self) -> bool {
+        let edges: Vec<Vec2> = self
+            .projected()
+            .windows(2)
+            .map(|p| p[1] - p[0])
+            .filter(|edge| edge.length() > 0.0)
+            .collect();
+        let direction = edges.iter().fold(Vec2::ZERO, |acc, e| acc + e.normalized());
+        edges.iter().all(|edge| edge.dot(direction) > 0.0)
     }
 }
 
-pub fn generate_bezier_curve(p0: Pos2, p1: Pos2, p2: Pos2, p3: Pos2, pixels: &mut Vec<Pixel>) {
-    let points = [p0, p1, p2, p3];
-    let max_distance = points
+pub fn segment_pieces(segments: &[BezierSegment]) -> Vec<CurvePiece> {
+    let count = segments.len() as f32;
+    segments
         .iter()
         .enumerate()
-        .flat_map(|(i, &p1)| points.iter().skip(i + 1).map(move |&p2| p1.distance(p2)))
-        .fold(0.0, f32::max);
-    let steps = (max_distance * SCALE).max(MIN_SCALE) as usize;
-    let bezier_matrix: [[f32; 4]; 4] = [
-        [-1.0, 3.0, -3.0, 1.0],
-        [3.0, -6.0, 3.0, 0.0],
-        [-3.0, 3.0, 0.0, 0.0],
-        [1.0, 0.0, 0.0, 0.0],
-    ];
-    let control_points_x = [p0.x, p1.x, p2.x, p3.x];
-    let control_points_y = [p0.y, p1.y, p2.y, p3.y];
+        .map(|(i, segment)| {
+            CurvePiece::from_segment(segment, i as f32 / count, (i + 1) as f32 / count)
+        })
+        .collect()
+}
 
-    for i in 0..=steps {
-        let t = i as f32 / steps as f32;
+// Bezier decomposition: raising every knot of the domain to multiplicity
+// `degree` leaves each non-empty span controlled by its own `degree + 1`
+// points, which are the rational Bezier points of that span.
+fn bezier_decomposition(
+    mut net: Vec<[f32; 3]>,
+    knots: &[f32],
+    degree: usize,
+) -> Vec<(Vec<[f32; 3]>, f32, f32)> {
+    let mut refined = knots.to_vec();
+    let mut breaks = knots[degree..=net.len()].to_vec();
+    breaks.dedup();
+    for u in breaks {
+        let multiplicity = refined.iter().filter(|k| **k == u).count();
+        for _ in multiplicity..degree {
+            insert_knot(&mut net, &mut refined, degree, u);
+        }
+    }
 
-        let t_vector = [t.powi(3), t.powi(2), t, 1.0];
+    (degree..net.len())
+        .filter(|&span| refined[span] < refined[span + 1])
+        .map(|span| {
+            let points = net[span - degree..=span].to_vec();
+            (points, refined[span], refined[span + 1])
+        })
+        .collect()
+}
 
-        let x_coeffs = matrix_multiply(&bezier_matrix, &control_points_x);
-        let y_coeffs = matrix_multiply(&bezier_matrix, &control_points_y);
+pub fn nurbs_pieces(
+    control_points: &[Pos2],
+    weights: &[f32],
+    knots: &[f32],
+    degree: usize,
+    kind: KnotVector,
+) -> Vec<CurvePiece> {
+    let (points, weights) = nurbs_control_net(control_points, weights, degree, kind);
+    if points.len() <= degree || knots.len() != points.len() + degree + 1 {
+        return vec![];
+    }
+    let (start, end) = nurbs_domain(knots, degree, points.len());
+    let global = |u: f32| (u - start) / (end - start);
+    bezier_decomposition(homogeneous(&points, &weights), knots, degree)
+        .into_iter()
+        .map(|(points, u0, u1)| CurvePiece {
+            points,
+            start: global(u0),
+            end: global(u1),
+        })
+        .collect()
+}
 
-        let x = multiply_coeffs(&x_coeffs, &t_vector);
-        let y = multiply_coeffs(&y_coeffs, &t_vector);
+#[derive(Clone, Copy, Debug)]
+pub struct Intersection {
+    pub point: Pos2,
+    pub first: f32,
+    pub second: f32,
+    pub tangential: bool,
+}
 
-        pixels.push(Pixel::new_black(x, y, 255));
+// Recursive bounding-box subdivision: pieces whose boxes overlap are halved
+// until both boxes shrink below LEAF_SIZE, which then counts as a hit.
+fn intersect_recursive(a: &CurvePiece, b: &CurvePiece, depth: u32, hits: &mut Vec<Intersection>) {
+    let (box_a, box_b) = (a.bounds(), b.bounds());
+    if hits.len() >= MAX_HITS || !box_a.expand(LEAF_SIZE).intersects(box_b) {
+        return;
+    }
+    let is_leaf = |r: Rect| r.width().max(r.height()) <= LEAF_SIZE;
+    if depth >= INTERSECTION_DEPTH || (is_leaf(box_a) && is_leaf(box_b)) {
+        let (chord_a, chord_b) = (a.chord(), b.chord());
+        let sine = if chord_a.length() > 0.0 && chord_b.length() > 0.0 {
+            chord_a.normalized().dot(chord_b.normalized().rot90()).abs()
+        } else {
+            1.0
+        };
+        hits.push(Intersection {
+            point: box_a.center().lerp(box_b.center(), 0.5),
+            first: (a.start + a.end) / 2.0,
+            second: (b.start + b.end) / 2.0,
+            tangential: sine < TANGENT_SINE,
+        });
+        return;
+    }
+    let (a1, a2) = a.split();
+    let (b1, b2) = b.split();
+    for a in [&a1, &a2] {
+        for b in [&b1, &b2] {
+            intersect_recursive(a, b, depth + 1, hits);
+        }
     }
 }
 
-fn matrix_multiply_4x4_2x4(matrix: &[[f32; 4]; 4], points: &[[f32; 2]; 4]) -> [[f32; 2]; 4] {
-    let mut result = [[0.0; 2]; 4];
-    for i in 0..4 {
-        for j in 0..4 {
-            result[i][0] += matrix[i][j] * points[j][0];
-            result[i][1] += matrix[i][j] * points[j][1];
+// A crossing leaves a cluster of neighbouring leaf hits, a tangential touch a
+// longer one; each cluster collapses into its average.
+fn merge_hits(hits: Vec<Intersection>) -> Vec<Intersection> {
+    let close = |a: &Intersection, b: &Intersection| {
+        a.point.distance(b.point) <= MERGE_DISTANCE
+            && (a.first - b.first).abs() <= MERGE_PARAMETER
+            && (a.second - b.second).abs() <= MERGE_PARAMETER
+    };
+    let mut clusters: Vec<Vec<Intersection>> = vec![];
+    for hit in hits {
+        let mut joined: Option<usize> = None;
+        let mut i = 0;
+        while i < clusters.len() {
+            if !clusters[i].iter().any(|other| close(other, &hit)) {
+                i += 1;
+                continue;
+            }
+            match joined {
+                None => {
+                    clusters[i].push(hit);
+                    joined = Some(i);
+                    i += 1;
+                }
+                Some(j) => {
+                    let cluster = clusters.remove(i);
+                    clusters[j].extend(cluster);
+                }
+            }
+        }
+        if joined.is_none() {
+            clusters.push(vec![hit]);
         }
     }
-    result
+
+    clusters
+        .iter()
+        .map(|cluster| {
+            let count = cluster.len() as f32;
+            let sum = cluster.iter().fold((Vec2::ZERO, 0.0, 0.0), |acc, hit| {
+                (
+                    acc.0 + hit.point.to_vec2(),
+                    acc.1 + hit.first,
+                    acc.2 + hit.second,
+                )
+            });
+            Intersection {
+                point: (sum.0 / count).to_pos2(),
+                first: sum.1 / count,
+                second: sum.2 / count,
+                tangential: cluster.iter().any(|hit| hit.tangential),
+            }
+        })
+        .collect()
 }
 
-pub fn generate_bspline_curve(control_points: &[Pos2], pixels: &mut Vec<Pixel>) {
-    let n = control_points.len();
-    if n < 4 {
-        panic!("At least 4 control points required.");
+pub fn intersect_pieces(first: &[CurvePiece], second: &[CurvePiece]) -> Vec<Intersection> {
+    let mut hits = vec![];
+    for a in first {
+        for b in second {
+            intersect_recursive(a, b, 0, &mut hits);
+        }
+    }
+    merge_hits(hits)
+}
+
+fn self_recursive(piece: &CurvePiece, depth: u32, hits: &mut Vec<Intersection>) {
+    if depth >= SELF_INTERSECTION_DEPTH || piece.is_monotone() {
+        return;
+    }
+    let (left, right) = piece.split();
+    self_recursive(&left, depth + 1, hits);
+    self_recursive(&right, depth + 1, hits);
+    intersect_recursive(&left, &right, 0, hits);
+}
+
+// Self-intersections pair up parts of one curve. Neighbouring parts always
+// meet at their shared parameter, and a closed curve meets itself at t = 0
+// and t = 1, so those hits are dropped.
+pub fn self_intersections(pieces: &[CurvePiece], closed: bool) -> Vec<Intersection> {
+    let mut hits = vec![];
+    for (i, piece) in pieces.iter().enumerate() {
+        self_recursive(piece, 0, &mut hits);
+        for other in &pieces[i + 1..] {
+            intersect_recursive(piece, other, 0, &mut hits);
+        }
+    }
+    merge_hits(hits)
+        .into_iter()
+        .filter(|hit| {
+            let (low, high) = (hit.first.min(hit.second), hit.first.max(hit.second));
+            let wraps = closed && low < SAME_PARAMETER && high > 1.0 - SAME_PARAMETER;
+            high - low > SAME_PARAMETER && !wraps
+        })
+        .collect()
+}
+
+const FIT_ITERATIONS: usize = 4;
+
+// Schneider's algorithm: fits one cubic per run of stroke points by least
+// squares with fixed end tangents, improves the parameters with Newton steps
+// and splits at the worst point while the error stays above `tolerance`.
+pub fn fit_bezier_path(stroke: &[Pos2], tolerance: f32) -> Vec<BezierSegment> {
+    let mut points = stroke.to_vec();
+    points.dedup_by(|a, b| a.distance(*b) < 1e-3);
+    if points.len() < 2 {
+        return vec![];
+    }
+    let last = points.len() - 1;
+    let start_tangent = (points[1] - points[0]).normalized();
+    let end_tangent = (points[last - 1] - points[last]).normalized();
+    let mut segments = vec![];
+    fit_cubic(
+        &points,
+        start_tangent,
+        end_tangent,
+        tolerance,
+        &mut segments,
+    );
+    segments
+}
+
+fn fit_cubic(
+    points: &[Pos2],
+    start_tangent: Vec2,
+    end_tangent: Vec2,
+    tolerance: f32,
+    segments: &mut Vec<BezierSegment>,
+) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    if points.len() == 2 {
+        let handle = first.distance(last) / 3.0;
+        segments.push(BezierSegment::new(vec![
+            first,
+            first + start_tangent * handle,
+            last + end_tangent * handle,
+            last,
+        ]));
+        return;
+    }
+
+    let mut parameters = chord_parameters(points);
+    let mut segment = fit_bezier(points, &parameters, start_tangent, end_tangent);
+    let (mut error, mut split) = max_fit_error(points, &segment, &parameters);
+    if error <= tolerance {
+        segments.push(segment);
+        return;
+    }
+    if error <= tolerance * 4.0 {
+        for _ in 0..FIT_ITERATIONS {
+            parameters = reparameterize(points, &segment, &parameters);
+            segment = fit_bezier(points, &parameters, start_tangent, end_tangent);
+            (error, split) = max_fit_error(points, &segment, &parameters);
+            if error <= tolerance {
+                segments.push(segment);
+                return;
+            }
+        }
+    }
+
+    // Where the stroke doubles back the chord vanishes; cross the turn instead.
+    let chord = points[split - 1] - points[split + 1];
+    let center = if chord.length() < 1e-3 {
+        let incoming = points[split] - points[split - 1];
+        Vec2::new(-incoming.y, incoming.x).normalized()
+    } else {
+        chord.normalized()
+    };
+    fit_cubic(
+        &points[..=split],
+        start_tangent,
+        center,
+        tolerance,
+        segments,
+    );
+    fit_cubic(&points[split..], -center, end_tangent, tolerance, segments);
+}
+
+fn chord_parameters(points: &[Pos2]) -> Vec<f32> {
+    let mut parameters = vec![0.0];
+    for pair in points.windows(2) {
+        parameters.push(parameters[parameters.len() - 1] + pair[0].distance(pair[1]));
+    }
+    let total = parameters[parameters.len() - 1];
+    parameters.iter().map(|u| u / total).collect()
+}
+
+// Least squares for the two handle lengths along the fixed tangents.
+fn fit_bezier(
+    points: &[Pos2],
+    parameters: &[f32],
+    start_tangent: Vec2,
+    end_tangent: Vec2,
+) -> BezierSegment {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let mut c = [[0.0; 2]; 2];
+    let mut x = [0.0; 2];
+    for (point, u) in points.iter().zip(parameters) {
+        let b = bernstein(*u);
+        let a1 = start_tangent * b[1];
+        let a2 = end_tangent * b[2];
+        c[0][0] += a1.dot(a1);
+        c[0][1] += a1.dot(a2);
+        c[1][1] += a2.dot(a2);
+        let tmp =
+            point.to_vec2() - (first.to_vec2() * (b[0] + b[1]) + last.to_vec2() * (b[2] + b[3]));
+        x[0] += a1.dot(tmp);
+        x[1] += a2.dot(tmp);
+    }
+    c[1][0] = c[0][1];
+
+    let det = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+    let (mut alpha1, mut alpha2) = if det.abs() > 1e-12 {
+        (
+            (x[0] * c[1][1] - x[1] * c[0][1]) / det,
+            (c[0][0] * x[1] - c[1][0] * x[0]) / det,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+    // Degenerate or backwards handles fall back to the Wu/Barsky heuristic.
+    let length = first.distance(last);
+    let epsilon = 1e-6 * length;
+    if alpha1 < epsilon || alpha2 < epsilon {
+        alpha1 = length / 3.0;
+        alpha2 = alpha1;
+    }
+    BezierSegment::new(vec![
+        first,
+        first + start_tangent * alpha1,
+        last + end_tangent * alpha2,
+        last,
+    ])
+}
+
+fn bernstein(u: f32) -> [f32; 4] {
+    let v = 1.0 - u;
+    [v * v * v, 3.0 * u * v * v, 3.0 * u * u * v, u * u * u]
+}
+
+fn max_fit_error(points: &[Pos2], segment: &BezierSegment, parameters: &[f32]) -> (f32, usize) {
+    let mut worst = (0.0, points.len() / 2);
+    for i in 1..points.len() - 1 {
+        let error = segment.point_at(parameters[i]).distance(points[i]);
+        if error >= worst.0 {
+            worst = (error, i);
+        }
     }
+    worst
+}
 
-    let max_distance = control_points
+// One Newton-Raphson step per point on (Q(u) - P) . Q'(u) = 0.
+fn reparameterize(points: &[Pos2], segment: &BezierSegment, parameters: &[f32]) -> Vec<f32> {
+    points
         .iter()
-        .enumerate()
-        .flat_map(|(i, &p1)| {
-            control_points
-                .iter()
-                .skip(i + 1)
-                .map(move |&p2| p1.distance(p2))
+        .zip(parameters)
+        .map(|(point, u)| {
+            let (q, first, second) = segment.derivatives_at(*u);
+            let offset = q - *point;
+            let numerator = offset.dot(first);
+            let denominator = first.dot(first) + offset.dot(second);
+            if denominator.abs() < 1e-12 {
+                *u
+            } else {
+                (u - numerator / denominator).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+// Cumulative chord length at `samples + 1` evenly spaced parameters; the
+// table maps curve parameter to travelled distance.
+pub fn arc_length_table(evaluate: impl Fn(f32) -> Pos2, samples: usize) -> Vec<(f32, f32)> {
+    let mut table = Vec::with_capacity(samples + 1);
+    let mut previous = evaluate(0.0);
+    let mut length = 0.0;
+    table.push((0.0, 0.0));
+    for i in 1..=samples {
+        let t = i as f32 / samples as f32;
+        let point = evaluate(t);
+        length += point.distance(previous);
+        previous = point;
+        table.push((t, length));
+    }
+    table
+}
+
+pub fn parameter_at_length(table: &[(f32, f32)], length: f32) -> f32 {
+    let index = table.partition_point(|(_, s)| *s < length);
+    if index == 0 {
+        return table[0].0;
+    }
+    if index >= table.len() {
+        return table[table.len() - 1].0;
+    }
+    let (t0, s0) = table[index - 1];
+    let (t1, s1) = table[index];
+    if s1 == s0 {
+        t0
+    } else {
+        t0 + (t1 - t0) * (length - s0) / (s1 - s0)
+    }
+}
+
+// Rational quadratic circle: nine control points on the circumscribed square
+// with corner weights of sqrt(2)/2 and doubled interior knots.
+pub fn nurbs_circle(center: Pos2, radius: f32) -> (Vec<Pos2>, Vec<f32>, Vec<f32>) {
+    let corner = std::f32::consts::FRAC_1_SQRT_2;
+    let offsets = [
+        (1.0, 0.0),
+        (1.0, 1.0),
+        (0.0, 1.0),
+        (-1.0, 1.0),
+        (-1.0, 0.0),
+        (-1.0, -1.0),
+        (0.0, -1.0),
+        (1.0, -1.0),
+        (1.0, 0.0),
+    ];
+    let points = offsets
+        .iter()
+        .map(|(dx, dy)| center + Vec2::new(*dx, *dy) * radius)
+        .collect();
+    let weights = (0..offsets.len())
+        .map(|i| if i % 2 == 1 { corner } else { 1.0 })
+        .collect();
+    let knots = vec![0.0, 0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0, 4.0];
+    (points, weights, knots)
+}
+
+// Signed curvature (x'y'' - y'x'') / |C'|^3; zero where the curve stalls.
+pub fn curvature(first: Vec2, second: Vec2) -> f32 {
+    let speed = first.length();
+    if speed < 1e-6 {
+        return 0.0;
+    }
+    (first.x * second.y - first.y * second.x) / (speed * speed * speed)
+}
+
+const REFINE_STEPS: u32 = 32;
+
+// Inflections are sign changes of C' x C''; curvature extrema are turning
+// points of the analytic curvature. Both are bracketed on `samples` evenly
+// spaced parameters and then refined inside the bracket.
+pub fn curvature_features(
+    evaluate: impl Fn(f32) -> Derivatives,
+    samples: usize,
+) -> (Vec<f32>, Vec<f32>) {
+    let cross = |t: f32| {
+        let (_, first, second) = evaluate(t);
+        first.x * second.y - first.y * second.x
+    };
+    let kappa = |t: f32| {
+        let (_, first, second) = evaluate(t);
+        curvature(first, second)
+    };
+    let params: Vec<f32> = (0..=samples).map(|i| i as f32 / samples as f32).collect();
+
+    let mut inflections = vec![];
+    for pair in params.windows(2) {
+        let (mut a, mut b) = (pair[0], pair[1]);
+        let (fa, fb) = (cross(a), cross(b));
+        if fa == 0.0 || fa.signum() == fb.signum() {
+            continue;
+        }
+        for _ in 0..REFINE_STEPS {
+            let middle = (a + b) / 2.0;
+            if cross(middle).signum() == fa.signum() {
+                a = middle;
+            } else {
+                b = middle;
+            }
+        }
+        inflections.push((a + b) / 2.0);
+    }
+
+    let values: Vec<f32> = params.iter().map(|t| kappa(*t)).collect();
+    let mut extrema = vec![];
+    for i in 1..samples {
+        let (before, after) = (values[i] - values[i - 1], values[i + 1] - values[i]);
+        if before.abs() < 1e-7 || after.abs() < 1e-7 || before.signum() == after.signum() {
+            continue;
+        }
+        // Ternary search for the maximum of sign * kappa inside the bracket.
+        let sign = before.signum();
+        let (mut a, mut b) = (params[i - 1], params[i + 1]);
+        for _ in 0..REFINE_STEPS {
+            let m1 = a + (b - a) / 3.0;
+            let m2 = b - (b - a) / 3.0;
+            if sign * kappa(m1) < sign * kappa(m2) {
+                a = m1;
+            } else {
+                b = m2;
+            }
+        }
+        extrema.push((a + b) / 2.0);
+    }
+    (inflections, extrema)
+}
+
+const OFFSET_DEPTH: u32 = 10;
+const OFFSET_SAMPLES: usize = 16;
+const OFFSET_CHECKS: usize = 6;
+
+// Point at `distance` along the left normal, and its derivative
+// C' * (1 - distance * kappa), which vanishes where the offset has a cusp.
+fn offset_point(derivatives: Derivatives, distance: f32) -> (Pos2, Vec2) {
+    let (point, first, second) = derivatives;
+    let direction = if first.length() < 1e-6 { second } else { first };
+    let normal = Vec2::new(-direction.y, direction.x).normalized();
+    let speed = 1.0 - distance * curvature(first, second);
+    (point + normal * distance, first * speed)
+}
+
+// Cubic Hermite interpolation of the exact offset on [t0, t1], halved until
+// it stays within `tolerance` of the offset at the check parameters.
+fn fit_offset(
+    evaluate: &impl Fn(f32) -> Derivatives,
+    (t0, t1): (f32, f32),
+    distance: f32,
+    tolerance: f32,
+    depth: u32,
+    segments: &mut Vec<(BezierSegment, f32, f32)>,
+) {
+    let (p0, d0) = offset_point(evaluate(t0), distance);
+    let (p1, d1) = offset_point(evaluate(t1), distance);
+    let scale = (t1 - t0) / 3.0;
+    let segment = BezierSegment::new(vec![p0, p0 + d0 * scale, p1 - d1 * scale, p1]);
+
+    let error = (1..OFFSET_CHECKS)
+        .map(|i| {
+            let s = i as f32 / OFFSET_CHECKS as f32;
+            let (exact, _) = offset_point(evaluate(t0 + (t1 - t0) * s), distance);
+            segment.point_at(s).distance(exact)
         })
         .fold(0.0, f32::max);
+    if error <= tolerance || depth >= OFFSET_DEPTH {
+        segments.push((segment, t0, t1));
+        return;
+    }
+    let middle = (t0 + t1) / 2.0;
+    fit_offset(
+        evaluate,
+        (t0, middle),
+        distance,
+        tolerance,
+        depth + 1,
+        segments,
+    );
+    fit_offset(
+        evaluate,
+        (middle, t1),
+        distance,
+        tolerance,
+        depth + 1,
+        segments,
+    );
+}
 
-    let steps = (max_distance * SCALE).max(MIN_SCALE) as usize;
-    let mut augmented_points = Vec::with_capacity(n + 3);
-    augmented_points.extend_from_slice(control_points);
-    augmented_points.push(control_points[0]);
-    augmented_points.push(control_points[1]);
-    augmented_points.push(control_points[2]);
-
-    let m_bspline = [
-        [-1.0 / 6.0, 3.0 / 6.0, -3.0 / 6.0, 1.0 / 6.0],
-        [3.0 / 6.0, -6.0 / 6.0, 3.0 / 6.0, 0.0],
-        [-3.0 / 6.0, 0.0, 3.0 / 6.0, 0.0],
-        [1.0 / 6.0, 4.0 / 6.0, 1.0 / 6.0, 0.0],
+// Part of `segment` between local parameters a <= b.
+fn sub_segment(segment: &BezierSegment, a: f32, b: f32) -> BezierSegment {
+    let (left, _) = segment.split(b);
+    if b <= 0.0 {
+        return left;
+    }
+    left.split(a / b).1
+}
+
+// Offset of the curve given by `evaluate` on [0, 1] as cubic Bezier segments.
+// `breaks` are the parameters where the curve is only C0 and must not be
+// fitted across. Cusps, where the distance reaches the radius of curvature,
+// also split the fit; the swallowtail loop between two cusps shows up as a
+// self-intersection whose parameter range contains a cusp, and is cut out.
+pub fn offset_segments(
+    evaluate: impl Fn(f32) -> Derivatives,
+    breaks: &[f32],
+    closed: bool,
+    distance: f32,
+    tolerance: f32,
+) -> Vec<BezierSegment> {
+    let speed = |t: f32| {
+        let (_, first, second) = evaluate(t);
+        1.0 - distance * curvature(first, second)
+    };
+    let mut cusps = vec![];
+    for span in breaks.windows(2) {
+        for i in 0..OFFSET_SAMPLES {
+            let step = (span[1] - span[0]) / OFFSET_SAMPLES as f32;
+            let (mut a, mut b) = (span[0] + step * i as f32, span[0] + step * (i + 1) as f32);
+            let sign = speed(a).signum();
+            if sign == speed(b).signum() {
+                continue;
+            }
+            for _ in 0..REFINE_STEPS {
+                let middle = (a + b) / 2.0;
+                if speed(middle).signum() == sign {
+                    a = middle;
+                } else {
+                    b = middle;
+                }
+            }
+            cusps.push((a + b) / 2.0);
+        }
+    }
+
+    let mut cuts: Vec<f32> = breaks
+        .iter()
+        .chain(cusps.iter())
+        .copied()
+        .filter(|t| t.is_finite())
+        .collect();
+    cuts.sort_by(f32::total_cmp);
+    cuts.dedup_by(|a, b| (*a - *b).abs() < 1e-6);
+    let mut fitted = vec![];
+    for span in cuts.windows(2) {
+        fit_offset(
+            &evaluate,
+            (span[0], span[1]),
+            distance,
+            tolerance,
+            0,
+            &mut fitted,
+        );
+    }
+
+    let pieces: Vec<CurvePiece> = fitted
+        .iter()
+        .map(|(segment, t0, t1)| CurvePiece::from_segment(segment, *t0, *t1))
+        .collect();
+    let mut removed: Vec<(f32, f32)> = self_intersections(&pieces, closed)
+        .iter()
+        .map(|hit| (hit.first.min(hit.second), hit.first.max(hit.second)))
+        .filter(|(a, b)| a.is_finite() && b.is_finite())
+        .filter(|(a, b)| cusps.iter().any(|c| a < c && c < b))
+        .collect();
+    removed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut kept = vec![];
+    let mut start = 0.0;
+    for (a, b) in removed {
+        if a > start {
+            kept.push((start, a));
+        }
+        start = start.max(b);
+    }
+    kept.push((start, 1.0));
+
+    let mut segments = vec![];
+    for (segment, t0, t1) in fitted {
+        for (a, b) in kept.iter() {
+            let (from, to) = (t0.max(*a), t1.min(*b));
+            if to - from > 1e-6 {
+                let local = |t: f32| (t - t0) / (t1 - t0);
+                segments.push(sub_segment(&segment, local(from), local(to)));
+            }
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTROL: [Pos2; 6] = [
+        Pos2::new(0.0, 0.0),
+        Pos2::new(20.0, 60.0),
+        Pos2::new(50.0, 70.0),
+        Pos2::new(80.0, 10.0),
+        Pos2::new(110.0, 40.0),
+        Pos2::new(130.0, 90.0),
     ];
+    const WEIGHTS: [f32; 6] = [1.0, 2.0, 0.5, 1.0, 3.0, 1.0];
 
-    for i in 0..n {
-        let p = [
-            [augmented_points[i].x, augmented_points[i].y],
-            [augmented_points[i + 1].x, augmented_points[i + 1].y],
-            [augmented_points[i + 2].x, augmented_points[i + 2].y],
-            [augmented_points[i + 3].x, augmented_points[i + 3].y],
-        ];
+    fn close(a: Pos2, b: Pos2) -> bool {
+        a.distance(b) < 1e-2
+    }
 
-        let g = matrix_multiply_4x4_2x4(&m_bspline, &p);
+    fn samples(start: f32, end: f32) -> impl Iterator<Item = f32> {
+        (0..=20).map(move |i| start + (end - start) * i as f32 / 20.0)
+    }
 
-        for j in 0..=steps {
-            let t = j as f32 / steps as f32;
-            let t_vec = [t.powi(3), t.powi(2), t, 1.0];
+    #[test]
+    fn knot_insertion_keeps_the_curve() {
+        let knots = nurbs_knots(KnotVector::Clamped, CONTROL.len(), 3);
+        let mut points = homogeneous(&CONTROL, &WEIGHTS);
+        let mut inserted = knots.clone();
+        insert_knot(&mut points, &mut inserted, 3, 1.5);
+        assert_eq!(points.len(), CONTROL.len() + 1);
+        let (control, weights): (Vec<Pos2>, Vec<f32>) = points
+            .iter()
+            .map(|p| (Pos2::new(p[0] / p[2], p[1] / p[2]), p[2]))
+            .unzip();
+        let (start, end) = nurbs_domain(&knots, 3, CONTROL.len());
+        for u in samples(start, end) {
+            let before = nurbs_point(&CONTROL, &WEIGHTS, &knots, 3, u);
+            let after = nurbs_point(&control, &weights, &inserted, 3, u);
+            assert!(close(before, after), "u = {}: {:?} {:?}", u, before, after);
+        }
+    }
 
-            let mut x = 0.0;
-            let mut y = 0.0;
-            for k in 0..4 {
-                x += t_vec[k] * g[k][0];
-                y += t_vec[k] * g[k][1];
+    #[test]
+    fn split_reproduces_both_halves() {
+        let knots = nurbs_knots(KnotVector::Clamped, CONTROL.len(), 3);
+        let (start, end) = nurbs_domain(&knots, 3, CONTROL.len());
+        let u = 1.3;
+        let (left, right) = split_nurbs(&CONTROL, &WEIGHTS, &knots, 3, u).unwrap();
+        for (half, from, to) in [(&left, start, u), (&right, u, end)] {
+            let (control, weights, half_knots) = half;
+            assert_eq!(nurbs_domain(half_knots, 3, control.len()), (from, to));
+            for v in samples(from, to) {
+                let whole = nurbs_point(&CONTROL, &WEIGHTS, &knots, 3, v);
+                let part = nurbs_point(control, weights, half_knots, 3, v);
+                assert!(close(whole, part), "u = {}: {:?} {:?}", v, whole, part);
             }
+        }
 
-            pixels.push(Pixel::new_black(x, y, 255));
+        let segment = BezierSegment::new(CONTROL[..4].to_vec());
+        let (first, second) = segment.split(0.3);
+        for t in samples(0.0, 1.0) {
+            assert!(close(first.point_at(t), segment.point_at(0.3 * t)));
+            assert!(close(second.point_at(t), segment.point_at(0.3 + 0.7 * t)));
+        }
+    }
+
+    #[test]
+    fn kochanek_bartels_at_zero_is_catmull_rom() {
+        let segments = kochanek_bartels_segments(&CONTROL, 0.0, 0.0, 0.0);
+        assert_eq!(segments.len(), CONTROL.len() - 1);
+        // Interior segments, where all four neighbours exist.
+        for i in 1..CONTROL.len() - 2 {
+            let [p0, p1, p2, p3] = [CONTROL[i - 1], CONTROL[i], CONTROL[i + 1], CONTROL[i + 2]];
+            let [p0, p1, p2, p3] = [p0.to_vec2(), p1.to_vec2(), p2.to_vec2(), p3.to_vec2()];
+            for t in samples(0.0, 1.0) {
+                let expected = 0.5
+                    * (2.0 * p1
+                        + (p2 - p0) * t
+                        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+                        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t);
+                assert!(close(segments[i].point_at(t), expected.to_pos2()));
+            }
+        }
+    }
+
+    #[test]
+    fn flattening_stays_within_tolerance() {
+        let segments = bspline_segments(&CONTROL, KnotVector::Clamped);
+        let polyline = flatten_segments(&segments, 0.25);
+        assert!(close(polyline[0], CONTROL[0]));
+        assert!(close(polyline[polyline.len() - 1], CONTROL[5]));
+        for t in samples(0.0, 1.0) {
+            let point = segments_point(&segments, t);
+            let distance = polyline
+                .windows(2)
+                .map(|edge| {
+                    let (a, b) = (edge[0], edge[1]);
+                    let along = ((point - a).dot(b - a) / (b - a).length_sq()).clamp(0.0, 1.0);
+                    point.distance(a + (b - a) * along)
+                })
+                .fold(f32::INFINITY, f32::min);
+            assert!(distance <= 0.25, "t = {}: {}", t, distance);
+        }
+    }
+
+    #[test]
+    fn fit_turns_where_stroke_doubles_back() {
+        let stroke = [
+            Pos2::new(0.0, -100.0),
+            Pos2::new(0.0, 0.0),
+            Pos2::new(100.0, 0.0),
+            Pos2::new(0.0, 0.0),
+            Pos2::new(0.0, 100.0),
+        ];
+        let segments = fit_bezier_path(&stroke, 0.5);
+        assert!(!segments.is_empty());
+        for segment in &segments {
+            let p = &segment.points;
+            assert!(p.iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+            // Every end keeps a tangent, including the ones at the turn.
+            assert!(p[0].distance(p[1]) > 1.0 && p[2].distance(p[3]) > 1.0);
         }
+        let end = segments[segments.len() - 1].end();
+        assert!(segments[0].start().distance(Pos2::new(0.0, -100.0)) < 1e-3);
+        assert!(end.distance(Pos2::new(0.0, 100.0)) < 1e-3);
     }
 }
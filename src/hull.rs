@@ -0,0 +1,640 @@
+use eframe::egui::{Pos2, Vec2};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HullAlgorithm {
+    Graham,
+    Jarvis,
+    Andrew,
+    QuickHull,
+    Chan,
+}
+
+impl HullAlgorithm {
+    pub fn variants() -> &'static [HullAlgorithm] {
+        &[
+            HullAlgorithm::Graham,
+            HullAlgorithm::Jarvis,
+            HullAlgorithm::Andrew,
+            HullAlgorithm::QuickHull,
+            HullAlgorithm::Chan,
+        ]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            HullAlgorithm::Graham => "Graham scan",
+            HullAlgorithm::Jarvis => "Jarvis march",
+            HullAlgorithm::Andrew => "Andrew's monotone chain",
+            HullAlgorithm::QuickHull => "QuickHull",
+            HullAlgorithm::Chan => "Chan",
+        }
+    }
+}
+
+// State of a hull run after one decision: the pivot it works around, the
+// chain of hull vertices kept so far, the point just considered and the
+// points thrown out up to now. Chan's algorithm also keeps the hulls of its
+// groups.
+#[derive(Clone, Debug, Default)]
+pub struct HullStep {
+    pub pivot: Option<Pos2>,
+    pub chain: Vec<Pos2>,
+    pub candidate: Option<Pos2>,
+    pub rejected: Vec<Pos2>,
+    pub groups: Vec<Vec<Pos2>>,
+    pub note: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hull {
+    pub algorithm: HullAlgorithm,
+    // Hull vertices with every turn to the same side as `turn` > 0;
+    // collinear points are left out.
+    pub points: Vec<Pos2>,
+    pub steps: Vec<HullStep>,
+    // Orientation tests made, the unit of work every algorithm shares.
+    pub operations: usize,
+}
+
+// Counts orientation tests and collects the steps of a run.
+#[derive(Default)]
+struct Recorder {
+    steps: Vec<HullStep>,
+    operations: usize,
+}
+
+impl Recorder {
+    // Positive when `b` lies to the left of the line from `o` through `a`.
+    fn turn(&mut self, o: Pos2, a: Pos2, b: Pos2) -> f32 {
+        self.operations += 1;
+        (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+    }
+
+    fn step(&mut self, step: HullStep) {
+        self.steps.push(step);
+    }
+}
+
+fn unique(points: &[Pos2]) -> Vec<Pos2> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    points
+}
+
+fn leftmost(points: &[Pos2]) -> Pos2 {
+    points
+        .iter()
+        .copied()
+        .min_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)))
+        .unwrap()
+}
+
+pub fn convex_hull(points: &[Pos2], algorithm: HullAlgorithm) -> Hull {
+    let points = unique(points);
+    let mut recorder = Recorder::default();
+    let hull = if points.len() < 3 {
+        points.clone()
+    } else {
+        match algorithm {
+            HullAlgorithm::Graham => graham(&points, &mut recorder),
+            HullAlgorithm::Jarvis => jarvis(&points, &mut recorder),
+            HullAlgorithm::Andrew => andrew(&points, &mut recorder, true),
+            HullAlgorithm::QuickHull => quickhull(&points, &mut recorder),
+            HullAlgorithm::Chan => chan(&points, &mut recorder),
+        }
+    };
+    recorder.step(HullStep {
+        chain: hull.clone(),
+        note: format!(
+            "{} hull vertices from {} points, {} orientation tests",
+            hull.len(),
+            points.len(),
+            recorder.operations
+        ),
+        ..Default::default()
+    });
+    Hull {
+        algorithm,
+        points: hull,
+        steps: recorder.steps,
+        operations: recorder.operations,
+    }
+}
+
+// Sorts the points by angle around the topmost one, then keeps a stack of
+// hull vertices and pops every vertex that does not make a left turn.
+fn graham(points: &[Pos2], recorder: &mut Recorder) -> Vec<Pos2> {
+    let pivot = points
+        .iter()
+        .copied()
+        .min_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+        .unwrap();
+    let mut sorted: Vec<Pos2> = points.iter().copied().filter(|p| *p != pivot).collect();
+    sorted.sort_by(|a, b| {
+        let turn = recorder.turn(pivot, *a, *b);
+        if turn > 0.0 {
+            std::cmp::Ordering::Less
+        } else if turn < 0.0 {
+            std::cmp::Ordering::Greater
+        } else {
+            pivot.distance(*a).total_cmp(&pivot.distance(*b))
+        }
+    });
+    recorder.step(HullStep {
+        pivot: Some(pivot),
+        chain: vec![pivot],
+        note: format!(
+            "Pivot ({:.1}, {:.1}), points sorted by angle",
+            pivot.x, pivot.y
+        ),
+        ..Default::default()
+    });
+
+    let mut stack = vec![pivot];
+    let mut rejected = vec![];
+    for point in sorted {
+        while stack.len() >= 2
+            && recorder.turn(stack[stack.len() - 2], stack[stack.len() - 1], point) <= 0.0
+        {
+            let popped = stack.pop().unwrap();
+            rejected.push(popped);
+            recorder.step(HullStep {
+                pivot: Some(pivot),
+                chain: stack.clone(),
+                candidate: Some(point),
+                rejected: rejected.clone(),
+                note: format!("({:.1}, {:.1}) popped: no left turn", popped.x, popped.y),
+                ..Default::default()
+            });
+        }
+        stack.push(point);
+        recorder.step(HullStep {
+            pivot: Some(pivot),
+            chain: stack.clone(),
+            candidate: Some(point),
+            rejected: rejected.clone(),
+            note: format!("({:.1}, {:.1}) pushed", point.x, point.y),
+            ..Default::default()
+        });
+    }
+    stack
+}
+
+// Gift wrapping: from the leftmost point, repeatedly picks the point with
+// every other point to its left, preferring the farthest on a tie.
+fn jarvis(points: &[Pos2], recorder: &mut Recorder) -> Vec<Pos2> {
+    let start = leftmost(points);
+    let mut hull = vec![];
+    let mut current = start;
+    loop {
+        hull.push(current);
+        let mut candidate = if points[0] == current {
+            points[1]
+        } else {
+            points[0]
+        };
+        let mut rejected = vec![];
+        for &point in points {
+            if point == current || point == candidate {
+                continue;
+            }
+            let turn = recorder.turn(current, candidate, point);
+            if turn < 0.0 || (turn == 0.0 && current.distance(point) > current.distance(candidate))
+            {
+                rejected.push(candidate);
+                candidate = point;
+            }
+        }
+        recorder.step(HullStep {
+            pivot: Some(current),
+            chain: hull.clone(),
+            candidate: Some(candidate),
+            rejected,
+            note: format!(
+                "From ({:.1}, {:.1}) wrap to ({:.1}, {:.1})",
+                current.x, current.y, candidate.x, candidate.y
+            ),
+            ..Default::default()
+        });
+        current = candidate;
+        if current == start || hull.len() > points.len() {
+            break;
+        }
+    }
+    hull
+}
+
+// Builds the lower and upper chains over the points sorted by x, popping
+// every vertex that does not make a left turn. `points` must be sorted and
+// free of duplicates.
+fn andrew(points: &[Pos2], recorder: &mut Recorder, record: bool) -> Vec<Pos2> {
+    let mut hull: Vec<Pos2> = vec![];
+    let mut rejected = vec![];
+    for (pass, sweep) in [
+        points.iter().collect::<Vec<_>>(),
+        points.iter().rev().collect(),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        // The upper chain may not pop into the finished lower one.
+        let floor = if pass == 0 { 2 } else { hull.len() + 1 };
+        for &point in sweep.into_iter().skip(pass) {
+            while hull.len() >= floor
+                && recorder.turn(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                rejected.push(hull.pop().unwrap());
+            }
+            hull.push(point);
+            if record {
+                recorder.step(HullStep {
+                    chain: hull.clone(),
+                    candidate: Some(point),
+                    rejected: rejected.clone(),
+                    note: format!(
+                        "{} chain: ({:.1}, {:.1}) added",
+                        if pass == 0 { "Lower" } else { "Upper" },
+                        point.x,
+                        point.y
+                    ),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    // The last point closes the loop back to the first.
+    hull.pop();
+    hull
+}
+
+// Splits the points by the line through the leftmost and rightmost ones,
+// then keeps adding the point farthest outside each hull edge; points inside
+// the triangle it forms are discarded.
+fn quickhull(points: &[Pos2], recorder: &mut Recorder) -> Vec<Pos2> {
+    let (a, b) = (points[0], points[points.len() - 1]);
+    let mut hull = vec![a, b];
+    let mut rejected = vec![];
+    let outside = |recorder: &mut Recorder, p: Pos2, q: Pos2, set: &[Pos2]| -> Vec<Pos2> {
+        set.iter()
+            .copied()
+            .filter(|x| recorder.turn(p, q, *x) < 0.0)
+            .collect()
+    };
+    let mut pending = vec![
+        (b, a, outside(recorder, b, a, points)),
+        (a, b, outside(recorder, a, b, points)),
+    ];
+    recorder.step(HullStep {
+        chain: hull.clone(),
+        note: format!(
+            "Split by the line from ({:.1}, {:.1}) to ({:.1}, {:.1})",
+            a.x, a.y, b.x, b.y
+        ),
+        ..Default::default()
+    });
+    while let Some((p, q, set)) = pending.pop() {
+        if set.is_empty() {
+            continue;
+        }
+        let farthest = set
+            .iter()
+            .copied()
+            .map(|x| (x, -recorder.turn(p, q, x)))
+            .max_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .unwrap()
+            .0;
+        let index = hull.iter().position(|x| *x == p).unwrap();
+        hull.insert(index + 1, farthest);
+        let left = outside(recorder, p, farthest, &set);
+        let right = outside(recorder, farthest, q, &set);
+        let discarded: Vec<Pos2> = set
+            .iter()
+            .copied()
+            .filter(|x| *x != farthest && !left.contains(x) && !right.contains(x))
+            .collect();
+        rejected.extend(&discarded);
+        recorder.step(HullStep {
+            pivot: Some(farthest),
+            chain: hull.clone(),
+            candidate: Some(farthest),
+            rejected: rejected.clone(),
+            note: format!(
+                "Farthest from the edge: ({:.1}, {:.1}), {} points discarded",
+                farthest.x,
+                farthest.y,
+                discarded.len()
+            ),
+            ..Default::default()
+        });
+        pending.push((farthest, q, right));
+        pending.push((p, farthest, left));
+    }
+    hull
+}
+
+// Hulls of groups of `m` points, then a Jarvis march over the groups that
+// gives up after `m` vertices; `m` is squared until the march closes.
+fn chan(points: &[Pos2], recorder: &mut Recorder) -> Vec<Pos2> {
+    let start = leftmost(points);
+    let mut m = 4usize;
+    loop {
+        m = m.min(points.len());
+        let groups: Vec<Vec<Pos2>> = points
+            .chunks(m)
+            .map(|group| {
+                if group.len() < 3 {
+                    group.to_vec()
+                } else {
+                    andrew(group, recorder, false)
+                }
+            })
+            .collect();
+        recorder.step(HullStep {
+            groups: groups.clone(),
+            note: format!("m = {}: hulls of {} groups", m, groups.len()),
+            ..Default::default()
+        });
+
+        let mut hull = vec![start];
+        let mut tangents = vec![0; groups.len()];
+        for _ in 0..m {
+            let current = hull[hull.len() - 1];
+            let mut candidates = vec![];
+            for (group, tangent) in groups.iter().zip(&mut tangents) {
+                *tangent = group_tangent(group, current, *tangent, recorder);
+                candidates.push(group[*tangent]);
+            }
+            let mut best: Option<Pos2> = None;
+            for &candidate in candidates.iter().filter(|c| **c != current) {
+                best = match best {
+                    Some(b) => {
+                        let turn = recorder.turn(current, b, candidate);
+                        if turn < 0.0
+                            || (turn == 0.0 && current.distance(candidate) > current.distance(b))
+                        {
+                            Some(candidate)
+                        } else {
+                            Some(b)
+                        }
+                    }
+                    None => Some(candidate),
+                };
+            }
+            let Some(next) = best else {
+                return hull;
+            };
+            recorder.step(HullStep {
+                pivot: Some(current),
+                chain: hull.clone(),
+                candidate: Some(next),
+                rejected: candidates.into_iter().filter(|c| *c != next).collect(),
+                groups: groups.clone(),
+                note: format!(
+                    "From ({:.1}, {:.1}) wrap to ({:.1}, {:.1}) among the group tangents",
+                    current.x, current.y, next.x, next.y
+                ),
+            });
+            if next == start {
+                return hull;
+            }
+            hull.push(next);
+        }
+        if m == points.len() {
+            return hull;
+        }
+        recorder.step(HullStep {
+            chain: hull,
+            groups,
+            note: format!("No closed hull after {} vertices, retrying", m),
+            ..Default::default()
+        });
+        m = m.saturating_mul(m);
+    }
+}
+
+// Index of the vertex of the convex `group` that has the whole group to its
+// left as seen from `from`, walking from the previous tangent `start`.
+fn group_tangent(group: &[Pos2], from: Pos2, start: usize, recorder: &mut Recorder) -> usize {
+    let n = group.len();
+    if let Some(index) = group.iter().position(|p| *p == from) {
+        return (index + 1) % n;
+    }
+    let better = |recorder: &mut Recorder, current: Pos2, other: Pos2| {
+        let turn = recorder.turn(from, current, other);
+        turn < 0.0 || (turn == 0.0 && from.distance(other) > from.distance(current))
+    };
+    let mut index = start % n;
+    for _ in 0..n {
+        let (next, previous) = ((index + 1) % n, (index + n - 1) % n);
+        if better(recorder, group[index], group[next]) {
+            index = next;
+        } else if better(recorder, group[index], group[previous]) {
+            index = previous;
+        } else {
+            break;
+        }
+    }
+    index
+}
+
+// Box around the hull with one side on a hull edge, and the hull vertices
+// touching its other sides.
+#[derive(Clone, Debug)]
+pub struct CaliperStep {
+    pub edge: [Pos2; 2],
+    pub antipodal: Pos2,
+    pub rectangle: [Pos2; 4],
+    pub note: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Calipers {
+    pub diameter: [Pos2; 2],
+    // From the narrowest edge straight across to its antipodal vertex.
+    pub width: [Pos2; 2],
+    pub min_area: [Pos2; 4],
+    pub min_perimeter: [Pos2; 4],
+    pub steps: Vec<CaliperStep>,
+}
+
+// Lengths of two adjacent sides.
+pub fn sides(rectangle: &[Pos2; 4]) -> (f32, f32) {
+    (
+        rectangle[0].distance(rectangle[1]),
+        rectangle[1].distance(rectangle[2]),
+    )
+}
+
+// Rotating calipers over a convex polygon with left turns, as produced by
+// `convex_hull`. Every edge in turn sets the angle of the calipers; the
+// opposite, leftmost and rightmost vertices only ever move forward, so all
+// queries take linear time. The narrowest strip and the smallest enclosing
+// rectangles, by area and by perimeter, all have a side on a hull edge.
+pub fn rotating_calipers(hull: &[Pos2]) -> Option<Calipers> {
+    let n = hull.len();
+    if n < 3 {
+        return None;
+    }
+    let vertex = |i: usize| hull[i % n];
+    let mut diameter = [hull[0], hull[1]];
+    let mut consider = |a: Pos2, b: Pos2| {
+        if a.distance(b) > diameter[0].distance(diameter[1]) {
+            diameter = [a, b];
+        }
+    };
+    let (mut far, mut right, mut left) = (1, 1, 0);
+    let mut steps: Vec<CaliperStep> = vec![];
+    let mut width: Option<(f32, [Pos2; 2])> = None;
+    let mut min_area: Option<(f32, [Pos2; 4])> = None;
+    let mut min_perimeter: Option<(f32, [Pos2; 4])> = None;
+    for i in 0..n {
+        let (a, b) = (vertex(i), vertex(i + 1));
+        let along = (b - a).normalized();
+        let across = Vec2::new(-along.y, along.x);
+        let height = |p: Pos2| (p - a).dot(across);
+        let offset = |p: Pos2| (p - a).dot(along);
+
+        for _ in 0..n {
+            if height(vertex(far + 1)) > height(vertex(far)) {
+                far += 1;
+            } else {
+                break;
+            }
+        }
+        for p in [vertex(far), vertex(far + 1)] {
+            consider(a, p);
+            consider(b, p);
+        }
+        for _ in 0..n {
+            if offset(vertex(right + 1)) > offset(vertex(right)) {
+                right += 1;
+            } else {
+                break;
+            }
+        }
+        if i == 0 {
+            left = far;
+        }
+        for _ in 0..n {
+            if offset(vertex(left + 1)) < offset(vertex(left)) {
+                left += 1;
+            } else {
+                break;
+            }
+        }
+
+        let antipodal = vertex(far);
+        let (h, min, max) = (
+            height(antipodal),
+            offset(vertex(left)),
+            offset(vertex(right)),
+        );
+        let corner = |x: f32, y: f32| a + along * x + across * y;
+        let rectangle = [
+            corner(min, 0.0),
+            corner(max, 0.0),
+            corner(max, h),
+            corner(min, h),
+        ];
+        let (area, perimeter) = ((max - min) * h, 2.0 * (max - min + h));
+        if width.is_none_or(|(w, _)| h < w) {
+            width = Some((h, [a + along * offset(antipodal), antipodal]));
+        }
+        if min_area.is_none_or(|(best, _)| area < best) {
+            min_area = Some((area, rectangle));
+        }
+        if min_perimeter.is_none_or(|(best, _)| perimeter < best) {
+            min_perimeter = Some((perimeter, rectangle));
+        }
+        steps.push(CaliperStep {
+            edge: [a, b],
+            antipodal,
+            rectangle,
+            note: format!(
+                "Edge {} of {}: width {:.2}, area {:.2}, perimeter {:.2}",
+                i + 1,
+                n,
+                h,
+                area,
+                perimeter
+            ),
+        });
+        far %= n;
+        right %= n;
+        left %= n;
+    }
+    Some(Calipers {
+        diameter,
+        width: width?.1,
+        min_area: min_area?.1,
+        min_perimeter: min_perimeter?.1,
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(points: &[Pos2]) -> Vec<Pos2> {
+        let mut points = points.to_vec();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        points
+    }
+
+    #[test]
+    fn algorithms_agree() {
+        let cloud = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(5.0, 12.0),
+            Pos2::new(0.0, 10.0),
+            // On the hull edges.
+            Pos2::new(5.0, 0.0),
+            Pos2::new(10.0, 5.0),
+            Pos2::new(2.5, 11.0),
+            Pos2::new(0.0, 5.0),
+            // Repeated.
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(5.0, 12.0),
+            // Inside, some of them on one line.
+            Pos2::new(3.0, 4.0),
+            Pos2::new(6.0, 7.0),
+            Pos2::new(2.0, 2.0),
+            Pos2::new(4.0, 4.0),
+            Pos2::new(6.0, 6.0),
+        ];
+        let expected = sorted(&[
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(5.0, 12.0),
+            Pos2::new(0.0, 10.0),
+        ]);
+        for algorithm in HullAlgorithm::variants() {
+            let hull = convex_hull(&cloud, *algorithm);
+            assert_eq!(sorted(&hull.points), expected, "{:?}", algorithm);
+        }
+    }
+
+    #[test]
+    fn calipers_on_rectangle() {
+        let rectangle = [
+            Pos2::new(1.0, 2.0),
+            Pos2::new(9.0, 2.0),
+            Pos2::new(9.0, 5.0),
+            Pos2::new(1.0, 5.0),
+        ];
+        let hull = convex_hull(&rectangle, HullAlgorithm::Andrew);
+        let calipers = rotating_calipers(&hull.points).unwrap();
+        let [a, b] = calipers.diameter;
+        assert!((a.distance(b) - 73.0f32.sqrt()).abs() < 1e-4);
+        let [a, b] = calipers.width;
+        assert!((a.distance(b) - 3.0).abs() < 1e-4);
+        let (first, second) = sides(&calipers.min_area);
+        assert!((first * second - 24.0).abs() < 1e-3);
+    }
+}
@@ -4,6 +4,9 @@ mod app;
 mod second_order_lines;
 mod lines;
 mod curves;
+mod polygons;
+mod hull;
+mod raster;
 use app::PaintApp;
 
 
@@ -0,0 +1,1406 @@
+use eframe::egui::{Color32, Pos2, Rect, Vec2};
+
+const PARALLEL_EPSILON: f32 = 1e-9;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ClipAlgorithm {
+    SutherlandHodgman,
+    WeilerAtherton,
+}
+
+impl ClipAlgorithm {
+    pub fn variants() -> &'static [ClipAlgorithm] {
+        &[
+            ClipAlgorithm::SutherlandHodgman,
+            ClipAlgorithm::WeilerAtherton,
+        ]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            ClipAlgorithm::SutherlandHodgman => "Sutherland–Hodgman",
+            ClipAlgorithm::WeilerAtherton => "Weiler–Atherton",
+        }
+    }
+}
+
+// Point where the subject boundary crosses the clip boundary.
+#[derive(Clone, Copy, Debug)]
+pub struct Crossing {
+    pub point: Pos2,
+    pub entering: bool,
+}
+
+// One stage of a clipping run: the clip edge being processed (Sutherland–
+// Hodgman) and the output built so far with the crossings known at that point.
+#[derive(Clone, Debug)]
+pub struct ClipStep {
+    pub edge: Option<[Pos2; 2]>,
+    pub polygons: Vec<Vec<Pos2>>,
+    pub crossings: Vec<Crossing>,
+    pub note: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Clipping {
+    pub algorithm: ClipAlgorithm,
+    pub steps: Vec<ClipStep>,
+    pub result: Vec<Vec<Pos2>>,
+}
+
+// Shoelace area, positive when the interior lies to the left of every edge
+// in the usual y-up orientation.
+pub fn signed_area(points: &[Pos2]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+pub fn perimeter(points: &[Pos2]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| points[i].distance(points[(i + 1) % n]))
+        .sum()
+}
+
+// Signed area and first moment of area of a closed contour.
+fn moments(points: &[Pos2]) -> (f32, Vec2) {
+    let n = points.len();
+    (0..n).fold((0.0, Vec2::ZERO), |(area, moment), i| {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let cross = a.x * b.y - b.x * a.y;
+        (
+            area + cross / 2.0,
+            moment + (a.to_vec2() + b.to_vec2()) * cross / 6.0,
+        )
+    })
+}
+
+// Center of mass of the region bounded by the contours; holes wound against
+// the outline subtract their area. Degenerate regions fall back to the
+// average of the vertices.
+pub fn centroid(contours: &[Vec<Pos2>]) -> Pos2 {
+    let (area, moment) = contours
+        .iter()
+        .map(|contour| moments(contour))
+        .fold((0.0, Vec2::ZERO), |(area, moment), (a, m)| {
+            (area + a, moment + m)
+        });
+    if area.abs() > PARALLEL_EPSILON {
+        return (moment / area).to_pos2();
+    }
+    let points: Vec<Pos2> = contours.iter().flatten().copied().collect();
+    let sum = points.iter().fold(Vec2::ZERO, |sum, p| sum + p.to_vec2());
+    (sum / points.len().max(1) as f32).to_pos2()
+}
+
+pub struct Measurements {
+    // Shoelace area of the outline, positive when it runs clockwise on screen.
+    pub signed_area: f32,
+    // Area of the outer contours less their holes.
+    pub area: f32,
+    pub perimeter: f32,
+    pub centroid: Pos2,
+    pub clockwise: bool,
+    pub convex: bool,
+    pub bounds: Rect,
+    pub vertices: usize,
+    pub holes: usize,
+}
+
+impl Measurements {
+    pub fn new(contours: &[Vec<Pos2>], convex: bool) -> Self {
+        let outline = contours.first().map_or(&[][..], |contour| &contour[..]);
+        let signed = signed_area(outline);
+        let area = contours
+            .iter()
+            .map(|contour| signed_area(contour))
+            .sum::<f32>();
+        Self {
+            signed_area: signed,
+            area: area.abs(),
+            perimeter: contours.iter().map(|contour| perimeter(contour)).sum(),
+            centroid: centroid(contours),
+            clockwise: signed > 0.0,
+            convex,
+            bounds: Rect::from_points(&contours.concat()),
+            vertices: contours.iter().map(Vec::len).sum(),
+            holes: nesting_depths(contours)
+                .iter()
+                .filter(|depth| *depth % 2 == 1)
+                .count(),
+        }
+    }
+}
+
+// Even-odd point in polygon test.
+pub fn contains(points: &[Pos2], point: Pos2) -> bool {
+    let n = points.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+// Copy of the polygon with a positive signed area.
+fn oriented(points: &[Pos2]) -> Vec<Pos2> {
+    let mut points = points.to_vec();
+    if signed_area(&points) < 0.0 {
+        points.reverse();
+    }
+    points
+}
+
+// Parameters along both segments where they cross, each in [0, 1). The
+// half-open range keeps a crossing through a shared vertex from being found
+// on both edges meeting there.
+fn segment_parameters(a0: Pos2, a1: Pos2, b0: Pos2, b1: Pos2) -> Option<(f32, f32)> {
+    let (da, db) = (a1 - a0, b1 - b0);
+    let denom = cross(da, db);
+    if denom.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+    let offset = b0 - a0;
+    let s = cross(offset, db) / denom;
+    let t = cross(offset, da) / denom;
+    ((0.0..1.0).contains(&s) && (0.0..1.0).contains(&t)).then_some((s, t))
+}
+
+pub fn clip_polygon(subject: &[Pos2], clip: &[Pos2], algorithm: ClipAlgorithm) -> Clipping {
+    let steps = match algorithm {
+        ClipAlgorithm::SutherlandHodgman => sutherland_hodgman(subject, clip),
+        ClipAlgorithm::WeilerAtherton => weiler_atherton(subject, clip),
+    };
+    let result = steps
+        .last()
+        .map(|step| step.polygons.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|polygon| polygon.len() >= 3)
+        .collect();
+    Clipping {
+        algorithm,
+        steps,
+        result,
+    }
+}
+
+// Clips the subject against each edge of the convex clip polygon in turn,
+// keeping the part on the inner side of the edge's line.
+fn sutherland_hodgman(subject: &[Pos2], clip: &[Pos2]) -> Vec<ClipStep> {
+    let clip = oriented(clip);
+    let n = clip.len();
+    let mut output = subject.to_vec();
+    let mut steps = vec![];
+    for i in 0..n {
+        let (a, b) = (clip[i], clip[(i + 1) % n]);
+        let side = |p: Pos2| cross(b - a, p - a);
+        let input = std::mem::take(&mut output);
+        let mut crossings = vec![];
+        for j in 0..input.len() {
+            let (start, end) = (input[(j + input.len() - 1) % input.len()], input[j]);
+            let (from, to) = (side(start), side(end));
+            if (from >= 0.0) != (to >= 0.0) {
+                let point = start + (end - start) * (from / (from - to));
+                output.push(point);
+                crossings.push(Crossing {
+                    point,
+                    entering: to >= 0.0,
+                });
+            }
+            if to >= 0.0 {
+                output.push(end);
+            }
+        }
+        steps.push(ClipStep {
+            edge: Some([a, b]),
+            note: format!(
+                "Clip edge {} of {}: {} vertices, {} crossings",
+                i + 1,
+                n,
+                output.len(),
+                crossings.len()
+            ),
+            polygons: vec![output.clone()],
+            crossings,
+        });
+    }
+    steps
+}
+
+struct Node {
+    point: Pos2,
+    crossing: Option<usize>,
+}
+
+// Subject or clip vertices with the crossings inserted in order along each
+// edge. Returns the list and the position of every crossing in it.
+fn crossing_list(
+    points: &[Pos2],
+    crossings: &[(usize, f32)],
+    count: usize,
+) -> (Vec<Node>, Vec<usize>) {
+    let mut nodes = vec![];
+    let mut positions = vec![0; count];
+    for (i, point) in points.iter().enumerate() {
+        nodes.push(Node {
+            point: *point,
+            crossing: None,
+        });
+        let mut on_edge: Vec<(f32, usize)> = crossings
+            .iter()
+            .enumerate()
+            .filter(|(_, (edge, _))| *edge == i)
+            .map(|(k, (_, t))| (*t, k))
+            .collect();
+        on_edge.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (_, k) in on_edge {
+            positions[k] = nodes.len();
+            nodes.push(Node {
+                point: Pos2::ZERO,
+                crossing: Some(k),
+            });
+        }
+    }
+    (nodes, positions)
+}
+
+// Walks the subject from each entering crossing, switching to the clip
+// boundary at every leaving crossing and back at the next entering one, so
+// concave polygons may produce several pieces.
+fn weiler_atherton(subject: &[Pos2], clip: &[Pos2]) -> Vec<ClipStep> {
+    let (subject, clip) = (oriented(subject), oriented(clip));
+    let mut found = vec![];
+    for i in 0..subject.len() {
+        let (s0, s1) = (subject[i], subject[(i + 1) % subject.len()]);
+        for j in 0..clip.len() {
+            let (c0, c1) = (clip[j], clip[(j + 1) % clip.len()]);
+            if let Some((s, t)) = segment_parameters(s0, s1, c0, c1) {
+                found.push((s0 + (s1 - s0) * s, (i, s), (j, t)));
+            }
+        }
+    }
+    let mut crossings = vec![];
+    let (mut on_subject, mut on_clip) = (vec![], vec![]);
+    for (point, (i, s), clip_edge) in found.iter().copied() {
+        // Neighbouring nodes along the subject decide the direction, so a
+        // boundary that only touches the clip at a vertex is dropped.
+        let (edge, limit) = if s > 0.0 {
+            (i, s)
+        } else {
+            ((i + subject.len() - 1) % subject.len(), 1.0)
+        };
+        let before = found
+            .iter()
+            .filter(|(_, (other_edge, other), _)| *other_edge == edge && *other < limit)
+            .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+            .map_or(subject[edge], |hit| hit.0);
+        let after = found
+            .iter()
+            .filter(|(_, (edge, other), _)| *edge == i && *other > s)
+            .min_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+            .map_or(subject[(i + 1) % subject.len()], |hit| hit.0);
+        let inside_before = contains(&clip, point + (before - point) / 2.0);
+        let inside_after = contains(&clip, point + (after - point) / 2.0);
+        if inside_before != inside_after {
+            crossings.push(Crossing {
+                point,
+                entering: inside_after,
+            });
+            on_subject.push((i, s));
+            on_clip.push(clip_edge);
+        }
+    }
+    let count = crossings.len();
+    let mut steps = vec![ClipStep {
+        edge: None,
+        polygons: vec![],
+        crossings: crossings.clone(),
+        note: format!("{} crossings classified", count),
+    }];
+    if count == 0 {
+        let inside = if contains(&clip, subject[0]) {
+            vec![subject]
+        } else if contains(&subject, clip[0]) {
+            vec![clip]
+        } else {
+            vec![]
+        };
+        steps.push(ClipStep {
+            edge: None,
+            polygons: inside,
+            crossings: vec![],
+            note: "No crossings: containment test".to_string(),
+        });
+        return steps;
+    }
+    let lists = [
+        crossing_list(&subject, &on_subject, count),
+        crossing_list(&clip, &on_clip, count),
+    ];
+    let mut visited = vec![false; count];
+    let mut polygons: Vec<Vec<Pos2>> = vec![];
+    for start in 0..count {
+        if visited[start] || !crossings[start].entering {
+            continue;
+        }
+        polygons.push(vec![]);
+        let (mut current, mut list) = (start, 0);
+        // Each crossing is left once along each boundary at most.
+        for _ in 0..2 * count {
+            visited[current] = true;
+            let polygon = polygons.last_mut().unwrap();
+            polygon.push(crossings[current].point);
+            let (nodes, positions) = &lists[list];
+            let mut position = (positions[current] + 1) % nodes.len();
+            while nodes[position].crossing.is_none() {
+                polygon.push(nodes[position].point);
+                position = (position + 1) % nodes.len();
+            }
+            let next = nodes[position].crossing.unwrap();
+            steps.push(ClipStep {
+                edge: None,
+                polygons: polygons.clone(),
+                crossings: crossings.clone(),
+                note: format!(
+                    "Follow {} from I{} to I{}",
+                    if list == 0 { "subject" } else { "clip" },
+                    current + 1,
+                    next + 1
+                ),
+            });
+            (current, list) = (next, 1 - list);
+            if current == start {
+                break;
+            }
+        }
+    }
+    steps
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BooleanOperation {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+impl BooleanOperation {
+    pub fn variants() -> &'static [BooleanOperation] {
+        &[
+            BooleanOperation::Union,
+            BooleanOperation::Intersection,
+            BooleanOperation::Difference,
+            BooleanOperation::Xor,
+        ]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            BooleanOperation::Union => "Union",
+            BooleanOperation::Intersection => "Intersection",
+            BooleanOperation::Difference => "Difference",
+            BooleanOperation::Xor => "XOR",
+        }
+    }
+}
+
+// Outer contour with the holes cut out of it.
+pub type Region = (Vec<Pos2>, Vec<Vec<Pos2>>);
+
+const DEGENERATE_DISTANCE: f32 = 1e-3;
+const PERTURBATION: f32 = 1e-2;
+const PERTURBATION_TRIES: usize = 8;
+const SLIVER_WIDTH: f32 = 0.1;
+
+// Even-odd test over every contour of a shape, so holes are outside.
+pub fn shape_contains(contours: &[Vec<Pos2>], point: Pos2) -> bool {
+    contours
+        .iter()
+        .filter(|contour| contains(contour, point))
+        .count()
+        % 2
+        == 1
+}
+
+fn distance_to_segment(point: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let edge = b - a;
+    let t = ((point - a).dot(edge) / edge.length_sq()).clamp(0.0, 1.0);
+    point.distance(a + edge * t)
+}
+
+// True when a vertex of either shape lies on the other's boundary, where
+// Greiner–Hormann cannot tell a crossing from a touch.
+fn is_degenerate(first: &[Vec<Pos2>], second: &[Vec<Pos2>]) -> bool {
+    let touches = |points: &[Vec<Pos2>], edges: &[Vec<Pos2>]| {
+        points.iter().flatten().any(|point| {
+            edges.iter().any(|contour| {
+                (0..contour.len()).any(|i| {
+                    let (a, b) = (contour[i], contour[(i + 1) % contour.len()]);
+                    distance_to_segment(*point, a, b) < DEGENERATE_DISTANCE
+                })
+            })
+        })
+    };
+    touches(first, second) || touches(second, first)
+}
+
+struct Vertex {
+    point: Pos2,
+    next: usize,
+    prev: usize,
+    neighbour: Option<usize>,
+    entry: bool,
+    visited: bool,
+}
+
+// Vertex list of a shape with the crossings inserted in order along each
+// edge, linked into one cycle per contour. Crossings are given as
+// (contour, edge, parameter); returns the list, the node of every crossing
+// and the first node of every contour.
+fn vertex_list(
+    contours: &[Vec<Pos2>],
+    crossings: &[(usize, usize, f32)],
+) -> (Vec<Vertex>, Vec<usize>, Vec<usize>) {
+    let mut nodes: Vec<Vertex> = vec![];
+    let mut positions = vec![0; crossings.len()];
+    let mut starts = vec![];
+    for (c, contour) in contours.iter().enumerate() {
+        let first = nodes.len();
+        starts.push(first);
+        for (i, point) in contour.iter().enumerate() {
+            let vertex = |point, neighbour| Vertex {
+                point,
+                next: 0,
+                prev: 0,
+                neighbour,
+                entry: false,
+                visited: false,
+            };
+            nodes.push(vertex(*point, None));
+            let mut on_edge: Vec<(f32, usize)> = crossings
+                .iter()
+                .enumerate()
+                .filter(|(_, (contour, edge, _))| *contour == c && *edge == i)
+                .map(|(k, (_, _, t))| (*t, k))
+                .collect();
+            on_edge.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let end = contour[(i + 1) % contour.len()];
+            for (t, k) in on_edge {
+                positions[k] = nodes.len();
+                nodes.push(vertex(*point + (end - *point) * t, Some(k)));
+            }
+        }
+        let last = nodes.len() - 1;
+        for (n, node) in nodes.iter_mut().enumerate().skip(first) {
+            node.next = if n == last { first } else { n + 1 };
+            node.prev = if n == first { last } else { n - 1 };
+        }
+    }
+    (nodes, positions, starts)
+}
+
+// Marks each crossing as entering or leaving the other shape, walking every
+// contour from a vertex whose side is known. `invert` swaps the two, which
+// makes the traversal follow the outside of the other shape.
+fn mark_entries(nodes: &mut [Vertex], starts: &[usize], other: &[Vec<Pos2>], invert: bool) {
+    for first in starts.iter().copied() {
+        let mut inside = shape_contains(other, nodes[first].point) != invert;
+        let mut node = first;
+        loop {
+            if nodes[node].neighbour.is_some() {
+                nodes[node].entry = !inside;
+                inside = !inside;
+            }
+            node = nodes[node].next;
+            if node == first {
+                break;
+            }
+        }
+    }
+}
+
+// Greiner–Hormann on shapes made of any number of contours, with holes and
+// self-touching contours following the even-odd rule. Contours that meet
+// nothing are kept or dropped by whether they lie inside the other shape.
+fn greiner_hormann(
+    subject: &[Vec<Pos2>],
+    clip: &[Vec<Pos2>],
+    invert_subject: bool,
+    invert_clip: bool,
+) -> Vec<Vec<Pos2>> {
+    let (mut on_subject, mut on_clip) = (vec![], vec![]);
+    for (a, first) in subject.iter().enumerate() {
+        for i in 0..first.len() {
+            let (s0, s1) = (first[i], first[(i + 1) % first.len()]);
+            for (b, second) in clip.iter().enumerate() {
+                for j in 0..second.len() {
+                    let (c0, c1) = (second[j], second[(j + 1) % second.len()]);
+                    if let Some((s, t)) = segment_parameters(s0, s1, c0, c1) {
+                        on_subject.push((a, i, s));
+                        on_clip.push((b, j, t));
+                    }
+                }
+            }
+        }
+    }
+    let (mut subject_nodes, subject_positions, subject_starts) = vertex_list(subject, &on_subject);
+    let (mut clip_nodes, clip_positions, clip_starts) = vertex_list(clip, &on_clip);
+    for k in 0..on_subject.len() {
+        subject_nodes[subject_positions[k]].neighbour = Some(clip_positions[k]);
+        clip_nodes[clip_positions[k]].neighbour = Some(subject_positions[k]);
+    }
+    mark_entries(&mut subject_nodes, &subject_starts, clip, invert_subject);
+    mark_entries(&mut clip_nodes, &clip_starts, subject, invert_clip);
+
+    let mut shapes = [subject_nodes, clip_nodes];
+    let mut contours = vec![];
+    while let Some(start) =
+        (0..shapes[0].len()).find(|n| shapes[0][*n].neighbour.is_some() && !shapes[0][*n].visited)
+    {
+        let (mut side, mut node) = (0, start);
+        let mut contour = vec![shapes[0][start].point];
+        // Every crossing is passed once on each shape at most.
+        for _ in 0..=2 * on_subject.len() {
+            let forward = shapes[side][node].entry;
+            shapes[side][node].visited = true;
+            loop {
+                let current = &shapes[side][node];
+                node = if forward { current.next } else { current.prev };
+                contour.push(shapes[side][node].point);
+                if shapes[side][node].neighbour.is_some() {
+                    break;
+                }
+            }
+            shapes[side][node].visited = true;
+            node = shapes[side][node].neighbour.unwrap();
+            side = 1 - side;
+            if shapes[side][node].visited {
+                break;
+            }
+        }
+        contour.pop();
+        contours.push(contour);
+    }
+
+    for (nodes, starts, contours_of, other, invert) in [
+        (&shapes[0], &subject_starts, subject, clip, invert_subject),
+        (&shapes[1], &clip_starts, clip, subject, invert_clip),
+    ] {
+        for (c, first) in starts.iter().enumerate() {
+            let mut node = *first;
+            let mut crossed = false;
+            loop {
+                crossed |= nodes[node].neighbour.is_some();
+                node = nodes[node].next;
+                if node == *first {
+                    break;
+                }
+            }
+            let contour = &contours_of[c];
+            if !crossed && shape_contains(other, contour[0]) != invert {
+                contours.push(contour.clone());
+            }
+        }
+    }
+    contours
+}
+
+// Shifts the clip shape by a growing amount until no vertex of either shape
+// lies on the other's boundary.
+fn perturbed(subject: &[Vec<Pos2>], clip: &[Vec<Pos2>]) -> Vec<Vec<Pos2>> {
+    let mut clip = clip.to_vec();
+    for attempt in 1..=PERTURBATION_TRIES {
+        if !is_degenerate(subject, &clip) {
+            break;
+        }
+        let angle = attempt as f32 * 2.4;
+        let shift = Vec2::angled(angle) * PERTURBATION * attempt as f32;
+        for point in clip.iter_mut().flatten() {
+            *point += shift;
+        }
+    }
+    clip
+}
+
+// Indices of the contours around each contour, tested at the middle of its
+// first edge.
+fn parents(contours: &[Vec<Pos2>]) -> Vec<Vec<usize>> {
+    let probe = |contour: &[Pos2]| contour[0] + (contour[1] - contour[0]) / 2.0;
+    contours
+        .iter()
+        .enumerate()
+        .map(|(i, contour)| {
+            (0..contours.len())
+                .filter(|j| *j != i && contains(&contours[*j], probe(contour)))
+                .collect()
+        })
+        .collect()
+}
+
+// Number of contours around each contour: even for outer contours and
+// islands, odd for holes.
+pub fn nesting_depths(contours: &[Vec<Pos2>]) -> Vec<usize> {
+    parents(contours).iter().map(Vec::len).collect()
+}
+
+// Splits contours into regions: a contour inside an odd number of others is
+// a hole of the smallest outer contour around it. Slivers left between
+// boundaries that coincided before perturbation are dropped by their mean
+// width.
+fn group_regions(contours: Vec<Vec<Pos2>>) -> Vec<Region> {
+    let contours: Vec<Vec<Pos2>> = contours
+        .into_iter()
+        .filter(|contour| {
+            contour.len() >= 3
+                && 2.0 * signed_area(contour).abs() > SLIVER_WIDTH * perimeter(contour)
+        })
+        .collect();
+    let parents = parents(&contours);
+    let is_hole = |i: usize| parents[i].len() % 2 == 1;
+    let mut regions: Vec<Region> = vec![];
+    let mut outer_index = vec![None; contours.len()];
+    for (i, contour) in contours.iter().enumerate() {
+        if !is_hole(i) {
+            outer_index[i] = Some(regions.len());
+            regions.push((contour.clone(), vec![]));
+        }
+    }
+    for (i, contour) in contours.iter().enumerate() {
+        if !is_hole(i) {
+            continue;
+        }
+        let owner = parents[i]
+            .iter()
+            .filter_map(|j| {
+                outer_index[*j].map(|region| (region, signed_area(&contours[*j]).abs()))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((region, _)) = owner {
+            regions[region].1.push(contour.clone());
+        }
+    }
+    regions
+}
+
+// Boolean operation between two shapes given as their contours. The result
+// may have several disjoint regions, each with its own holes.
+pub fn boolean(
+    subject: &[Vec<Pos2>],
+    clip: &[Vec<Pos2>],
+    operation: BooleanOperation,
+) -> Vec<Region> {
+    let clip = perturbed(subject, clip);
+    let contours = match operation {
+        BooleanOperation::Intersection => greiner_hormann(subject, &clip, false, false),
+        BooleanOperation::Union => greiner_hormann(subject, &clip, true, true),
+        BooleanOperation::Difference => greiner_hormann(subject, &clip, true, false),
+        BooleanOperation::Xor => {
+            let mut contours = greiner_hormann(subject, &clip, true, false);
+            contours.extend(greiner_hormann(&clip, subject, true, false));
+            contours
+        }
+    };
+    group_regions(contours)
+}
+
+const COLLINEAR_EPSILON: f32 = 1e-6;
+
+#[derive(Clone, Copy, Debug)]
+pub enum EarTest {
+    Ear,
+    Collinear,
+    Reflex,
+    Contains(Pos2),
+}
+
+// One candidate ear: the vertex with its neighbours, the outcome of the test
+// and the polygon still to be triangulated when it was tried.
+#[derive(Clone, Debug)]
+pub struct EarStep {
+    pub candidate: [Pos2; 3],
+    pub test: EarTest,
+    pub remaining: Vec<Pos2>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Triangulation {
+    pub triangles: Vec<[Pos2; 3]>,
+    pub steps: Vec<EarStep>,
+    pub complete: bool,
+}
+
+impl Triangulation {
+    pub fn area(&self) -> f32 {
+        self.triangles.iter().map(|t| signed_area(t).abs()).sum()
+    }
+}
+
+fn in_triangle(point: Pos2, [a, b, c]: [Pos2; 3]) -> bool {
+    cross(b - a, point - a) >= 0.0
+        && cross(c - b, point - b) >= 0.0
+        && cross(a - c, point - c) >= 0.0
+}
+
+// Ear clipping of a simple polygon: a convex vertex whose triangle holds no
+// other vertex is cut off until three remain. Collinear vertices are dropped
+// without a triangle. Stops early, leaving `complete` unset, when a full pass
+// finds no ear, which happens only for self-intersecting input.
+pub fn ear_clipping(points: &[Pos2]) -> Triangulation {
+    let mut remaining = oriented(points);
+    let (mut triangles, mut steps) = (vec![], vec![]);
+    let mut index = 0;
+    let mut misses = 0;
+    while remaining.len() > 3 && misses < remaining.len() {
+        let n = remaining.len();
+        let candidate = [
+            remaining[(index + n - 1) % n],
+            remaining[index % n],
+            remaining[(index + 1) % n],
+        ];
+        let [a, b, c] = candidate;
+        let turn = cross(b - a, c - b);
+        let test = if turn.abs() < COLLINEAR_EPSILON {
+            EarTest::Collinear
+        } else if turn < 0.0 {
+            EarTest::Reflex
+        } else if let Some(point) = remaining
+            .iter()
+            .find(|p| !candidate.contains(p) && in_triangle(**p, candidate))
+        {
+            EarTest::Contains(*point)
+        } else {
+            EarTest::Ear
+        };
+        steps.push(EarStep {
+            candidate,
+            test,
+            remaining: remaining.clone(),
+        });
+        match test {
+            EarTest::Ear | EarTest::Collinear => {
+                if let EarTest::Ear = test {
+                    triangles.push(candidate);
+                }
+                remaining.remove(index % n);
+                index %= n - 1;
+                misses = 0;
+            }
+            EarTest::Reflex | EarTest::Contains(_) => {
+                index = (index + 1) % n;
+                misses += 1;
+            }
+        }
+    }
+    let complete = remaining.len() == 3;
+    if complete {
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+    }
+    Triangulation {
+        triangles,
+        steps,
+        complete,
+    }
+}
+
+const DUPLICATE_DISTANCE: f32 = 1e-3;
+const COLLINEAR_SINE: f32 = 1e-3;
+
+#[derive(Clone, Debug)]
+pub struct Validation {
+    // Crossing points with the two edges meeting there.
+    pub crossings: Vec<(Pos2, usize, usize)>,
+    pub duplicates: Vec<usize>,
+    pub collinear: Vec<usize>,
+    // Contours wound against their nesting: a hole running like the outline,
+    // or a separate part running like a hole.
+    pub misnested: Vec<usize>,
+    pub clockwise: bool,
+}
+
+impl Validation {
+    pub fn is_simple(&self) -> bool {
+        self.crossings.is_empty() && self.duplicates.is_empty() && self.misnested.is_empty()
+    }
+}
+
+// Orientation as seen on screen, where y grows downwards.
+pub fn is_clockwise(points: &[Pos2]) -> bool {
+    signed_area(points) > 0.0
+}
+
+// Crossing points between edges that do not share a vertex.
+fn self_crossings(points: &[Pos2]) -> Vec<(Pos2, usize, usize)> {
+    let n = points.len();
+    let mut crossings = vec![];
+    for i in 0..n {
+        for j in i + 2..n {
+            if i == 0 && j == n - 1 {
+                continue;
+            }
+            let (a0, a1) = (points[i], points[(i + 1) % n]);
+            let (b0, b1) = (points[j], points[(j + 1) % n]);
+            if let Some((s, _)) = segment_parameters(a0, a1, b0, b1) {
+                crossings.push((a0 + (a1 - a0) * s, i, j));
+            }
+        }
+    }
+    crossings
+}
+
+pub fn validate(points: &[Pos2]) -> Validation {
+    let n = points.len();
+    let duplicates = (0..n)
+        .filter(|i| points[*i].distance(points[(i + 1) % n]) < DUPLICATE_DISTANCE)
+        .collect();
+    let collinear = (0..n)
+        .filter(|i| {
+            let (a, b, c) = (points[(i + n - 1) % n], points[*i], points[(i + 1) % n]);
+            a.distance(b) >= DUPLICATE_DISTANCE
+                && b.distance(c) >= DUPLICATE_DISTANCE
+                && cross(b - a, c - b).abs() < COLLINEAR_SINE * (b - a).length() * (c - b).length()
+        })
+        .collect();
+    Validation {
+        crossings: self_crossings(points),
+        duplicates,
+        collinear,
+        misnested: vec![],
+        clockwise: is_clockwise(points),
+    }
+}
+
+// Validation of a shape with several contours, with vertex and edge indices
+// running over the contours one after another. Edges of different contours
+// must not cross either.
+pub fn validate_contours(contours: &[Vec<Pos2>]) -> Validation {
+    let clockwise = contours
+        .first()
+        .is_some_and(|outline| is_clockwise(outline));
+    let mut validation = Validation {
+        crossings: vec![],
+        duplicates: vec![],
+        collinear: vec![],
+        misnested: vec![],
+        clockwise,
+    };
+    let mut offsets = vec![];
+    let mut offset = 0;
+    for contour in contours {
+        let single = validate(contour);
+        let shift = |indices: Vec<usize>| indices.into_iter().map(move |i| i + offset);
+        validation.crossings.extend(
+            single
+                .crossings
+                .into_iter()
+                .map(|(point, i, j)| (point, i + offset, j + offset)),
+        );
+        validation.duplicates.extend(shift(single.duplicates));
+        validation.collinear.extend(shift(single.collinear));
+        offsets.push(offset);
+        offset += contour.len();
+    }
+    for (a, first) in contours.iter().enumerate() {
+        for (b, second) in contours.iter().enumerate().skip(a + 1) {
+            let (n, m) = (first.len(), second.len());
+            for i in 0..n {
+                for j in 0..m {
+                    let (a0, a1) = (first[i], first[(i + 1) % n]);
+                    let (b0, b1) = (second[j], second[(j + 1) % m]);
+                    if let Some((s, _)) = segment_parameters(a0, a1, b0, b1) {
+                        let point = a0 + (a1 - a0) * s;
+                        validation
+                            .crossings
+                            .push((point, offsets[a] + i, offsets[b] + j));
+                    }
+                }
+            }
+        }
+    }
+    validation.misnested = nesting_depths(contours)
+        .iter()
+        .enumerate()
+        .filter(|(i, depth)| (is_clockwise(&contours[*i]) == clockwise) != (*depth % 2 == 0))
+        .map(|(i, _)| i)
+        .collect();
+    validation
+}
+
+// Drops repeated and collinear vertices.
+pub fn simplify(points: &[Pos2]) -> Vec<Pos2> {
+    let mut points = points.to_vec();
+    loop {
+        let validation = validate(&points);
+        let Some(index) = validation
+            .duplicates
+            .first()
+            .or(validation.collinear.first())
+            .copied()
+        else {
+            return points;
+        };
+        points.remove(index);
+        if points.len() < 3 {
+            return points;
+        }
+    }
+}
+
+// Cuts a self-intersecting polygon at every crossing into simple loops by
+// leaving each crossing along the other edge, then nests the loops into
+// regions so the result covers what the even-odd fill covers.
+pub fn split_at_crossings(points: &[Pos2]) -> Vec<Region> {
+    let points = simplify(points);
+    let n = points.len();
+    let crossings = self_crossings(&points);
+    // (edge, parameter along it, crossing) for both edges of every crossing.
+    let mut on_edges = vec![];
+    for (k, (point, i, j)) in crossings.iter().enumerate() {
+        for edge in [*i, *j] {
+            let (a, b) = (points[edge], points[(edge + 1) % n]);
+            on_edges.push((edge, (*point - a).dot(b - a) / (b - a).length_sq(), k));
+        }
+    }
+    on_edges.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let mut nodes: Vec<(Pos2, Option<usize>)> = vec![];
+    let mut on_edge = on_edges.iter().peekable();
+    for (i, point) in points.iter().enumerate() {
+        nodes.push((*point, None));
+        while let Some((_, _, k)) = on_edge.next_if(|(edge, _, _)| *edge == i) {
+            nodes.push((crossings[*k].0, Some(*k)));
+        }
+    }
+    let twin = |node: usize| {
+        let crossing = nodes[node].1?;
+        (0..nodes.len()).find(|other| *other != node && nodes[*other].1 == Some(crossing))
+    };
+    let mut used = vec![false; nodes.len()];
+    let mut loops = vec![];
+    for start in 0..nodes.len() {
+        if used[start] {
+            continue;
+        }
+        let mut contour = vec![];
+        let mut node = start;
+        while !used[node] {
+            used[node] = true;
+            contour.push(nodes[node].0);
+            let next = (node + 1) % nodes.len();
+            node = twin(next).unwrap_or(next);
+        }
+        loops.push(simplify(&contour));
+    }
+    group_regions(loops)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FillRule {
+    EvenOdd,
+    NonZero,
+    Positive,
+    Negative,
+}
+
+impl FillRule {
+    pub fn variants() -> &'static [FillRule] {
+        &[
+            FillRule::EvenOdd,
+            FillRule::NonZero,
+            FillRule::Positive,
+            FillRule::Negative,
+        ]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            FillRule::EvenOdd => "Even-odd",
+            FillRule::NonZero => "Non-zero",
+            FillRule::Positive => "Positive",
+            FillRule::Negative => "Negative",
+        }
+    }
+
+    pub fn is_filled(self, winding: i32) -> bool {
+        match self {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+            FillRule::Positive => winding > 0,
+            FillRule::Negative => winding < 0,
+        }
+    }
+
+    // Fraction of a pixel filled given its accumulated signed area, the
+    // antialiased counterpart of `is_filled`.
+    pub fn coverage(self, winding: f32) -> f32 {
+        match self {
+            FillRule::EvenOdd => {
+                let folded = winding.abs() % 2.0;
+                if folded > 1.0 {
+                    2.0 - folded
+                } else {
+                    folded
+                }
+            }
+            FillRule::NonZero => winding.abs().min(1.0),
+            FillRule::Positive => winding.clamp(0.0, 1.0),
+            FillRule::Negative => (-winding).clamp(0.0, 1.0),
+        }
+    }
+}
+
+// Crossings of the horizontal line at `y` sorted by x, each +1 where the
+// edge runs down the screen and -1 where it runs up.
+pub fn scanline_crossings(contours: &[Vec<Pos2>], y: f32) -> Vec<(f32, i32)> {
+    let mut crossings = vec![];
+    for contour in contours {
+        let n = contour.len();
+        for i in 0..n {
+            let (a, b) = (contour[i], contour[(i + 1) % n]);
+            if (a.y <= y) != (b.y <= y) {
+                let x = a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y);
+                crossings.push((x, if b.y > a.y { 1 } else { -1 }));
+            }
+        }
+    }
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+    crossings
+}
+
+// Sum of the crossings left of the point: positive inside contours that run
+// counterclockwise on screen.
+pub fn winding_number(contours: &[Vec<Pos2>], point: Pos2) -> i32 {
+    scanline_crossings(contours, point.y)
+        .iter()
+        .take_while(|(x, _)| *x < point.x)
+        .map(|(_, direction)| direction)
+        .sum()
+}
+
+// Spans between consecutive crossings of the line at `y` with the winding
+// number inside each.
+pub fn scanline_spans(contours: &[Vec<Pos2>], y: f32) -> Vec<(f32, f32, i32)> {
+    let crossings = scanline_crossings(contours, y);
+    let mut winding = 0;
+    crossings
+        .windows(2)
+        .map(|pair| {
+            winding += pair[0].1;
+            (pair[0].0, pair[1].0, winding)
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pattern {
+    Checker,
+    Dots,
+    Bricks,
+    Diagonal,
+}
+
+impl Pattern {
+    pub fn variants() -> &'static [Pattern] {
+        &[
+            Pattern::Checker,
+            Pattern::Dots,
+            Pattern::Bricks,
+            Pattern::Diagonal,
+        ]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Pattern::Checker => "Checker",
+            Pattern::Dots => "Dots",
+            Pattern::Bricks => "Bricks",
+            Pattern::Diagonal => "Diagonal",
+        }
+    }
+
+    // 8x8 tile, one byte per row with the leftmost pixel in the high bit.
+    fn tile(self) -> [u8; 8] {
+        match self {
+            Pattern::Checker => [0xF0, 0xF0, 0xF0, 0xF0, 0x0F, 0x0F, 0x0F, 0x0F],
+            Pattern::Dots => [0x00, 0x18, 0x3C, 0x18, 0x00, 0x00, 0x00, 0x00],
+            Pattern::Bricks => [0xFF, 0x80, 0x80, 0x80, 0xFF, 0x08, 0x08, 0x08],
+            Pattern::Diagonal => [0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01],
+        }
+    }
+
+    fn is_set(self, x: i32, y: i32) -> bool {
+        let row = self.tile()[y.rem_euclid(8) as usize];
+        row & (0x80 >> x.rem_euclid(8)) != 0
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FillStyle {
+    Solid(Color32),
+    LinearGradient {
+        from: Color32,
+        to: Color32,
+        // Direction of the gradient in degrees.
+        angle: f32,
+    },
+    RadialGradient {
+        inner: Color32,
+        outer: Color32,
+    },
+    Hatch {
+        color: Color32,
+        angle: f32,
+        spacing: f32,
+    },
+    Pattern {
+        pattern: Pattern,
+        color: Color32,
+    },
+}
+
+impl FillStyle {
+    pub fn variants() -> [FillStyle; 5] {
+        [
+            FillStyle::Solid(Color32::BLACK),
+            FillStyle::LinearGradient {
+                from: Color32::RED,
+                to: Color32::BLUE,
+                angle: 0.0,
+            },
+            FillStyle::RadialGradient {
+                inner: Color32::WHITE,
+                outer: Color32::DARK_GREEN,
+            },
+            FillStyle::Hatch {
+                color: Color32::BLACK,
+                angle: 45.0,
+                spacing: 8.0,
+            },
+            FillStyle::Pattern {
+                pattern: Pattern::Checker,
+                color: Color32::BLACK,
+            },
+        ]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            FillStyle::Solid(..) => "Solid",
+            FillStyle::LinearGradient { .. } => "Linear gradient",
+            FillStyle::RadialGradient { .. } => "Radial gradient",
+            FillStyle::Hatch { .. } => "Hatch",
+            FillStyle::Pattern { .. } => "Pattern",
+        }
+    }
+
+    // Color of the pixel at `pos` inside a shape bounded by `bounds`; None
+    // leaves the pixel unpainted.
+    pub fn color_at(&self, pos: Pos2, bounds: Rect) -> Option<Color32> {
+        match *self {
+            FillStyle::Solid(color) => Some(color),
+            FillStyle::LinearGradient { from, to, angle } => {
+                let direction = Vec2::angled(angle.to_radians());
+                let (low, high) = bounds_extent(bounds, direction);
+                let t = (direction.dot(pos.to_vec2()) - low) / (high - low).max(1.0);
+                Some(from.lerp_to_gamma(to, t.clamp(0.0, 1.0)))
+            }
+            FillStyle::RadialGradient { inner, outer } => {
+                let radius = (bounds.size().length() / 2.0).max(1.0);
+                let t = pos.distance(bounds.center()) / radius;
+                Some(inner.lerp_to_gamma(outer, t.clamp(0.0, 1.0)))
+            }
+            FillStyle::Hatch {
+                color,
+                angle,
+                spacing,
+            } => {
+                let normal = Vec2::angled(angle.to_radians()).rot90();
+                let offset = normal.dot(pos.to_vec2()).rem_euclid(spacing.max(1.0));
+                (offset < 1.0).then_some(color)
+            }
+            FillStyle::Pattern { pattern, color } => pattern
+                .is_set(pos.x.floor() as i32, pos.y.floor() as i32)
+                .then_some(color),
+        }
+    }
+}
+
+fn bounds_extent(bounds: Rect, direction: Vec2) -> (f32, f32) {
+    [
+        bounds.left_top(),
+        bounds.right_top(),
+        bounds.left_bottom(),
+        bounds.right_bottom(),
+    ]
+    .iter()
+    .map(|corner| direction.dot(corner.to_vec2()))
+    .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), t| {
+        (low.min(t), high.max(t))
+    })
+}
+
+// Hatch lines at `angle` degrees, `spacing` apart, clipped to the area the
+// fill rule fills. The contours are turned so the hatch lines become
+// scanlines; the lines sit on a grid shared by every shape.
+pub fn hatch_lines(
+    contours: &[Vec<Pos2>],
+    rule: FillRule,
+    angle: f32,
+    spacing: f32,
+) -> Vec<[Pos2; 2]> {
+    let along = Vec2::angled(angle.to_radians());
+    // A proper rotation, so the winding numbers keep their sign.
+    let across = Vec2::new(-along.y, along.x);
+    let turned: Vec<Vec<Pos2>> = contours
+        .iter()
+        .map(|contour| {
+            contour
+                .iter()
+                .map(|p| Pos2::new(along.dot(p.to_vec2()), across.dot(p.to_vec2())))
+                .collect()
+        })
+        .collect();
+    let back = |x: f32, y: f32| (along * x + across * y).to_pos2();
+    let (low, high) = turned
+        .iter()
+        .flatten()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), p| {
+            (low.min(p.y), high.max(p.y))
+        });
+    let spacing = spacing.max(1.0);
+    let mut lines = vec![];
+    let mut y = (low / spacing).ceil() * spacing;
+    while y < high {
+        for (start, end, winding) in scanline_spans(&turned, y) {
+            if rule.is_filled(winding) {
+                lines.push([back(start, y), back(end, y)]);
+            }
+        }
+        y += spacing;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Pos2> {
+        vec![
+            Pos2::new(x, y),
+            Pos2::new(x + size, y),
+            Pos2::new(x + size, y + size),
+            Pos2::new(x, y + size),
+        ]
+    }
+
+    fn area(regions: &[Region]) -> f32 {
+        regions
+            .iter()
+            .map(|(outer, holes)| {
+                signed_area(outer).abs() - holes.iter().map(|h| signed_area(h).abs()).sum::<f32>()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn clipping_overlapping_squares() {
+        for algorithm in ClipAlgorithm::variants() {
+            let clipping =
+                clip_polygon(&square(0.0, 0.0, 10.0), &square(5.0, 5.0, 10.0), *algorithm);
+            let total: f32 = clipping.result.iter().map(|p| signed_area(p).abs()).sum();
+            assert!((total - 25.0).abs() < 1e-3, "{:?}: {}", algorithm, total);
+        }
+    }
+
+    #[test]
+    fn boolean_of_overlapping_squares() {
+        let (a, b) = (vec![square(0.0, 0.0, 10.0)], vec![square(5.0, 5.0, 10.0)]);
+        for (operation, expected) in [
+            (BooleanOperation::Union, 175.0),
+            (BooleanOperation::Intersection, 25.0),
+            (BooleanOperation::Difference, 75.0),
+            (BooleanOperation::Xor, 150.0),
+        ] {
+            let total = area(&boolean(&a, &b, operation));
+            assert!((total - expected).abs() < 0.5, "{:?}: {}", operation, total);
+        }
+    }
+
+    #[test]
+    fn square_with_hole() {
+        let regions = boolean(
+            &[square(0.0, 0.0, 10.0)],
+            &[square(3.0, 3.0, 4.0)],
+            BooleanOperation::Difference,
+        );
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].1.len(), 1);
+        assert!((area(&regions) - 84.0).abs() < 0.5);
+
+        let mut contours = vec![square(0.0, 0.0, 10.0), square(3.0, 3.0, 4.0)];
+        assert_eq!(nesting_depths(&contours), vec![0, 1]);
+        // Wound like the outline, the hole is reported and the rules differ.
+        assert_eq!(validate_contours(&contours).misnested, vec![1]);
+        let (inside, hole) = (Pos2::new(1.0, 5.0), Pos2::new(5.0, 5.0));
+        assert_eq!(winding_number(&contours, inside).abs(), 1);
+        assert_eq!(winding_number(&contours, hole).abs(), 2);
+        assert!(!FillRule::EvenOdd.is_filled(winding_number(&contours, hole)));
+        assert!(FillRule::NonZero.is_filled(winding_number(&contours, hole)));
+
+        contours[1].reverse();
+        assert!(validate_contours(&contours).is_simple());
+        assert_eq!(winding_number(&contours, hole), 0);
+    }
+
+    #[test]
+    fn bow_tie_splits_in_two() {
+        let bow_tie = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(0.0, 10.0),
+        ];
+        assert!(!validate(&bow_tie).is_simple());
+        let regions = split_at_crossings(&bow_tie);
+        assert_eq!(regions.len(), 2);
+        for (outer, _) in &regions {
+            assert!((signed_area(outer).abs() - 25.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn ear_clipping_covers_concave_polygon() {
+        // U shape with a collinear vertex on its bottom edge.
+        let shape = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(5.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(7.0, 10.0),
+            Pos2::new(7.0, 3.0),
+            Pos2::new(3.0, 3.0),
+            Pos2::new(3.0, 10.0),
+            Pos2::new(0.0, 10.0),
+        ];
+        let triangulation = ear_clipping(&shape);
+        assert!(triangulation.complete);
+        assert!((triangulation.area() - signed_area(&shape).abs()).abs() < 1e-3);
+    }
+}
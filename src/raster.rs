@@ -0,0 +1,435 @@
+use crate::pixel::Pixel;
+use crate::polygons::FillRule;
+use eframe::egui::{vec2, Color32, ColorImage, Pos2, Rect};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+impl Connectivity {
+    pub fn variants() -> &'static [Connectivity] {
+        &[Connectivity::Four, Connectivity::Eight]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Connectivity::Four => "4-connected",
+            Connectivity::Eight => "8-connected",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SeedRule {
+    // Spreads until it meets the boundary color.
+    Boundary,
+    // Spreads over the color found under the seed.
+    Flood,
+}
+
+impl SeedRule {
+    pub fn variants() -> &'static [SeedRule] {
+        &[SeedRule::Boundary, SeedRule::Flood]
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            SeedRule::Boundary => "Boundary fill",
+            SeedRule::Flood => "Flood fill",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SeedFillSettings {
+    pub rule: SeedRule,
+    pub connectivity: Connectivity,
+    pub boundary: Color32,
+    // Largest per-channel difference still counted as the same color.
+    pub tolerance: u8,
+    pub color: Color32,
+}
+
+impl Default for SeedFillSettings {
+    fn default() -> Self {
+        Self {
+            rule: SeedRule::Boundary,
+            connectivity: Connectivity::Four,
+            boundary: Color32::BLACK,
+            tolerance: 64,
+            color: Color32::RED,
+        }
+    }
+}
+
+// Colors of the canvas, one per point, read back from a screenshot.
+pub struct Raster {
+    pub origin: Pos2,
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Color32>,
+}
+
+impl Raster {
+    // Samples the screenshot at the center of every point of `rect`.
+    pub fn from_screenshot(image: &ColorImage, rect: Rect, pixels_per_point: f32) -> Self {
+        let (width, height) = (rect.width() as usize, rect.height() as usize);
+        let [image_width, image_height] = image.size;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let px = ((rect.min.x + x as f32 + 0.5) * pixels_per_point) as usize;
+                let py = ((rect.min.y + y as f32 + 0.5) * pixels_per_point) as usize;
+                let (px, py) = (px.min(image_width - 1), py.min(image_height - 1));
+                pixels.push(image.pixels[py * image_width + px]);
+            }
+        }
+        Self {
+            origin: rect.min,
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color32 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn cell(&self, pos: Pos2) -> Option<(usize, usize)> {
+        let offset = pos - self.origin;
+        let (x, y) = (offset.x.floor(), offset.y.floor());
+        (x >= 0.0 && y >= 0.0 && (x as usize) < self.width && (y as usize) < self.height)
+            .then_some((x as usize, y as usize))
+    }
+}
+
+// No channel differs by more than `tolerance`.
+pub fn similar(a: Color32, b: Color32, tolerance: u8) -> bool {
+    a.to_array()
+        .iter()
+        .zip(b.to_array())
+        .all(|(a, b)| a.abs_diff(b) <= tolerance)
+}
+
+// Whether a pixel belongs to the region grown from `seed`.
+fn region(
+    raster: &Raster,
+    seed: Pos2,
+    settings: SeedFillSettings,
+) -> impl Fn(&Raster, usize, usize) -> bool {
+    let target = raster.cell(seed).map(|(x, y)| raster.get(x, y));
+    move |raster: &Raster, x: usize, y: usize| {
+        let current = raster.get(x, y);
+        match (settings.rule, target) {
+            (SeedRule::Boundary, _) => !similar(current, settings.boundary, settings.tolerance),
+            (SeedRule::Flood, Some(target)) => similar(current, target, settings.tolerance),
+            (SeedRule::Flood, None) => false,
+        }
+    }
+}
+
+// Seed fill over the raster one pixel at a time: every step fills the pixel
+// on top of the stack and pushes its neighbours.
+pub fn pixel_fill(
+    raster: Raster,
+    seed: Pos2,
+    settings: SeedFillSettings,
+) -> impl Iterator<Item = Vec<Pixel>> {
+    let fillable = region(&raster, seed, settings);
+    let neighbours: &[(isize, isize)] = match settings.connectivity {
+        Connectivity::Four => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+        Connectivity::Eight => &[
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ],
+    };
+    let mut filled = vec![false; raster.width * raster.height];
+    let mut stack: Vec<(usize, usize)> = raster.cell(seed).into_iter().collect();
+    let color = settings.color;
+    let rgba = (color.r(), color.g(), color.b(), color.a());
+    std::iter::from_fn(move || {
+        while let Some((x, y)) = stack.pop() {
+            if filled[y * raster.width + x] || !fillable(&raster, x, y) {
+                continue;
+            }
+            filled[y * raster.width + x] = true;
+            for (dx, dy) in neighbours {
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(*dx), y.checked_add_signed(*dy))
+                else {
+                    continue;
+                };
+                if nx < raster.width && ny < raster.height {
+                    stack.push((nx, ny));
+                }
+            }
+            let pos = raster.origin + vec2(x as f32, y as f32);
+            return Some(vec![Pixel::new_pos2(pos, rgba)]);
+        }
+        None
+    })
+}
+
+// Span seed fill over the raster. Every step fills one horizontal run and
+// queues the runs it touches on the rows above and below; 8-connectivity
+// widens the search by one pixel to reach diagonal neighbours.
+pub fn seed_fill(
+    raster: Raster,
+    seed: Pos2,
+    settings: SeedFillSettings,
+) -> impl Iterator<Item = Vec<Pixel>> {
+    let fillable = region(&raster, seed, settings);
+    let mut filled = vec![false; raster.width * raster.height];
+    let mut stack: Vec<(usize, usize)> = raster.cell(seed).into_iter().collect();
+    let color = settings.color;
+    let rgba = (color.r(), color.g(), color.b(), color.a());
+    std::iter::from_fn(move || {
+        while let Some((x, y)) = stack.pop() {
+            let index = |x: usize| y * raster.width + x;
+            if filled[index(x)] || !fillable(&raster, x, y) {
+                continue;
+            }
+            let (mut left, mut right) = (x, x);
+            while left > 0 && !filled[index(left - 1)] && fillable(&raster, left - 1, y) {
+                left -= 1;
+            }
+            while right + 1 < raster.width
+                && !filled[index(right + 1)]
+                && fillable(&raster, right + 1, y)
+            {
+                right += 1;
+            }
+            let mut span = vec![];
+            for x in left..=right {
+                filled[index(x)] = true;
+                let pos = raster.origin + vec2(x as f32, y as f32);
+                span.push(Pixel::new_pos2(pos, rgba));
+            }
+
+            let (from, to) = match settings.connectivity {
+                Connectivity::Four => (left, right),
+                Connectivity::Eight => (left.saturating_sub(1), (right + 1).min(raster.width - 1)),
+            };
+            let rows = [
+                y.checked_sub(1),
+                Some(y + 1).filter(|row| *row < raster.height),
+            ];
+            for row in rows.into_iter().flatten() {
+                // One seed per run of open pixels is enough.
+                let mut in_run = false;
+                for x in from..=to {
+                    let open = !filled[row * raster.width + x] && fillable(&raster, x, row);
+                    if open && !in_run {
+                        stack.push((x, row));
+                    }
+                    in_run = open;
+                }
+            }
+            return Some(span);
+        }
+        None
+    })
+}
+
+// Per-pixel coverage of a shape, in the pixels of `width` x `height` cells
+// starting at `origin`.
+pub struct Coverage {
+    pub origin: (i32, i32),
+    pub width: usize,
+    pub height: usize,
+    values: Vec<f32>,
+}
+
+impl Coverage {
+    pub fn row(&self, y: usize) -> &[f32] {
+        &self.values[y * self.width..(y + 1) * self.width]
+    }
+}
+
+// Signed-area accumulation rasterizer. Every edge adds, to each pixel it
+// crosses, the area it leaves to its right and carries the rest of its
+// height to the next pixel; a running sum along the row then gives the
+// winding number of every pixel as a fraction of its area. The areas are
+// the exact overlap of the shape with each pixel cell [x, x + 1) x [y, y + 1),
+// so two shapes sharing an edge add up to full coverage with no seam or
+// overlap.
+pub fn coverage(contours: &[Vec<Pos2>], rule: FillRule) -> Coverage {
+    let points = contours.iter().flatten();
+    let (min, max) = points.fold(
+        (
+            Pos2::new(f32::INFINITY, f32::INFINITY),
+            Pos2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+        ),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+    );
+    if min.x > max.x {
+        return Coverage {
+            origin: (0, 0),
+            width: 0,
+            height: 0,
+            values: vec![],
+        };
+    }
+    let origin = (min.x.floor() as i32, min.y.floor() as i32);
+    let width = (max.x.ceil() as i32 - origin.0 + 1) as usize;
+    let height = (max.y.ceil() as i32 - origin.1) as usize;
+    // Two spare columns take the area spilled past the right end.
+    let stride = width + 2;
+    let mut accumulation = vec![0.0f32; stride * height];
+
+    let shift = vec2(origin.0 as f32, origin.1 as f32);
+    for contour in contours {
+        let n = contour.len();
+        for i in 0..n {
+            let (a, b) = (contour[i] - shift, contour[(i + 1) % n] - shift);
+            if a.y == b.y {
+                continue;
+            }
+            let (direction, top, bottom) = if a.y < b.y { (1.0, a, b) } else { (-1.0, b, a) };
+            let slope = (bottom.x - top.x) / (bottom.y - top.y);
+            let mut x = top.x;
+            let first = top.y.max(0.0) as usize;
+            let last = (bottom.y.ceil() as usize).min(height);
+            for y in first..last {
+                let row = &mut accumulation[y * stride..(y + 1) * stride];
+                let dy = (y as f32 + 1.0).min(bottom.y) - (y as f32).max(top.y);
+                let x_next = x + slope * dy;
+                let d = dy * direction;
+                let (left, right) = if x < x_next { (x, x_next) } else { (x_next, x) };
+                let left_floor = left.floor();
+                let left_index = left_floor as usize;
+                let right_ceil = right.ceil();
+                let right_index = right_ceil as usize;
+                if right_index <= left_index + 1 {
+                    // Within one pixel: split by the mean position.
+                    let middle = 0.5 * (x + x_next) - left_floor;
+                    row[left_index] += d - d * middle;
+                    row[left_index + 1] += d * middle;
+                } else {
+                    let s = 1.0 / (right - left);
+                    let left_fraction = left - left_floor;
+                    let a0 = 0.5 * s * (1.0 - left_fraction).powi(2);
+                    let right_fraction = right - right_ceil + 1.0;
+                    let am = 0.5 * s * right_fraction.powi(2);
+                    row[left_index] += d * a0;
+                    if right_index == left_index + 2 {
+                        row[left_index + 1] += d * (1.0 - a0 - am);
+                    } else {
+                        let a1 = s * (1.5 - left_fraction);
+                        row[left_index + 1] += d * (a1 - a0);
+                        for cell in &mut row[left_index + 2..right_index - 1] {
+                            *cell += d * s;
+                        }
+                        let a2 = a1 + (right_index - left_index - 3) as f32 * s;
+                        row[right_index - 1] += d * (1.0 - a2 - am);
+                    }
+                    row[right_index] += d * am;
+                }
+                x = x_next;
+            }
+        }
+    }
+
+    let mut values = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let mut winding = 0.0;
+        for x in 0..width {
+            winding += accumulation[y * stride + x];
+            values.push(rule.coverage(winding));
+        }
+    }
+    Coverage {
+        origin,
+        width,
+        height,
+        values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Pos2> {
+        vec![
+            Pos2::new(x, y),
+            Pos2::new(x + size, y),
+            Pos2::new(x + size, y + size),
+            Pos2::new(x, y + size),
+        ]
+    }
+
+    fn at(coverage: &Coverage, x: i32, y: i32) -> f32 {
+        let (x, y) = (x - coverage.origin.0, y - coverage.origin.1);
+        if x < 0 || y < 0 || x as usize >= coverage.width || y as usize >= coverage.height {
+            return 0.0;
+        }
+        coverage.row(y as usize)[x as usize]
+    }
+
+    #[test]
+    fn unit_square_covers_its_area() {
+        let coverage = coverage(&[square(3.0, 5.0, 1.0)], FillRule::NonZero);
+        assert!((total(&coverage) - 1.0).abs() < 1e-5);
+        assert!((at(&coverage, 3, 5) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn shared_edge_leaves_no_seam() {
+        let left = coverage(&[square(0.5, 0.0, 2.0)], FillRule::NonZero);
+        let right = coverage(&[square(2.5, 0.0, 2.0)], FillRule::NonZero);
+        for y in 0..2 {
+            let seam = at(&left, 2, y) + at(&right, 2, y);
+            assert!((seam - 1.0).abs() < 1e-5, "row {}: {}", y, seam);
+        }
+    }
+
+    fn total(coverage: &Coverage) -> f32 {
+        (0..coverage.height)
+            .flat_map(|y| coverage.row(y).iter())
+            .sum()
+    }
+
+    // Edges shallower than one pixel per row spread over several cells.
+    #[test]
+    fn sloped_triangle_covers_its_area() {
+        let triangle = vec![
+            Pos2::new(0.3, 0.2),
+            Pos2::new(9.7, 2.9),
+            Pos2::new(2.1, 6.6),
+        ];
+        let area = crate::polygons::signed_area(&triangle).abs();
+        let coverage = coverage(&[triangle], FillRule::NonZero);
+        assert!((total(&coverage) - area).abs() < 1e-3);
+        assert!((0..coverage.height)
+            .flat_map(|y| coverage.row(y).iter())
+            .all(|v| (-1e-5..=1.0 + 1e-5).contains(v)));
+    }
+
+    #[test]
+    fn shared_diagonal_leaves_no_seam() {
+        let (a, b) = (Pos2::new(0.4, 0.5), Pos2::new(9.6, 3.5));
+        let below = coverage(
+            &[vec![a, b, Pos2::new(9.6, 6.0), Pos2::new(0.4, 6.0)]],
+            FillRule::NonZero,
+        );
+        let above = coverage(
+            &[vec![a, Pos2::new(0.4, 0.0), Pos2::new(9.6, 0.0), b]],
+            FillRule::NonZero,
+        );
+        for y in 0..4 {
+            for x in 1..9 {
+                let seam = at(&below, x, y) + at(&above, x, y);
+                assert!((seam - 1.0).abs() < 1e-4, "({}, {}): {}", x, y, seam);
+            }
+        }
+    }
+}